@@ -0,0 +1,249 @@
+use std::fmt::Display;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// Something the user can do by pressing keys. The names are used in the
+/// configuration file, the descriptions are displayed in the help overlay.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    /// quit loghawk
+    Quit,
+
+    /// display the key bindings
+    Help,
+
+    /// open the command line
+    CommandLine,
+
+    /// move down one row
+    Down,
+
+    /// move up one row
+    Up,
+
+    /// move down one page
+    PageDown,
+
+    /// move up one page
+    PageUp,
+
+    /// move down half a page
+    HalfPageDown,
+
+    /// move up half a page
+    HalfPageUp,
+
+    /// go to the first row, or to the row given by the count
+    FirstRow,
+
+    /// go to the last row, or to the row given by the count
+    LastRow,
+
+    /// go to the next interval in the timeline
+    NextBucket,
+
+    /// go to the previous interval in the timeline
+    PreviousBucket,
+
+    /// show or hide the timeline
+    ToggleTimeline,
+
+    /// only display rows which contain a text
+    Grep,
+
+    /// only display rows with the value of the focused column
+    PivotOnColumn,
+
+    /// only display rows which contain the value of the focused cell
+    PivotOnValue,
+
+    /// return to the view before the last pivot
+    Back,
+
+    /// scroll to the right
+    ScrollRight,
+
+    /// scroll to the left
+    ScrollLeft,
+
+    /// focus the next column
+    NextColumn,
+
+    /// focus the previous column
+    PreviousColumn,
+
+    /// display statistics of the focused column
+    ColumnStats,
+
+    /// switch between original, UTC and local timestamps
+    ToggleTimestamps,
+
+    /// display long rows over multiple lines instead of cutting them
+    ToggleWrap,
+
+    /// display unicode characters which are not ASCII as escape sequences
+    /// and highlight look-alike words
+    ToggleMaskUnicode,
+
+    /// list values with bidi controls, invisible characters or words which
+    /// look like other words
+    ShowSuspicious,
+
+    /// display the current row as it has been found in the file
+    ToggleRawLine,
+
+    /// display all rows as they have been found in the file
+    ToggleRawView,
+
+    /// display the bytes of the current row as hex dump
+    HexDump,
+
+    /// go to the next tab
+    NextTab,
+
+    /// go to the previous tab
+    PreviousTab,
+
+    /// go to the tab given by the count or the pressed digit, or to the
+    /// next tab
+    SelectTab,
+
+    /// focus the other pane
+    SwitchPane,
+
+    /// keep both panes at the same time
+    ToggleSync,
+
+    /// bookmark the current row, or remove its bookmark
+    ToggleBookmark,
+
+    /// list bookmarks and marks
+    ShowBookmarks,
+
+    /// mark the current row with the letter typed next
+    SetMark,
+
+    /// go to the row marked with the letter typed next
+    JumpToMark,
+
+    /// go to the next bookmark
+    NextBookmark,
+
+    /// go to the previous bookmark
+    PreviousBookmark,
+
+    /// start or end selecting rows
+    ToggleSelection,
+
+    /// discard the selection
+    ClearSelection,
+
+    /// edit the note of the current row
+    EditNote,
+
+    /// copy the current row to the clipboard
+    YankRow,
+
+    /// copy the focused cell to the clipboard
+    YankCell,
+
+    /// copy the selected rows to the clipboard
+    YankSelection,
+}
+
+/// Groups of actions in the help overlay
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Category {
+    General,
+    Navigation,
+    Search,
+    Columns,
+    Inspect,
+    Tabs,
+    Marks,
+    Export,
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::General => "general",
+            Self::Navigation => "navigation",
+            Self::Search => "search",
+            Self::Columns => "columns",
+            Self::Inspect => "inspection",
+            Self::Tabs => "tabs and panes",
+            Self::Marks => "bookmarks and selection",
+            Self::Export => "export",
+        };
+        f.write_str(name)
+    }
+}
+
+impl Action {
+    /// Name of the action in the configuration file, e.g. `half-page-down`
+    pub fn name(&self) -> String {
+        self.to_possible_value()
+            .map(|value| value.get_name().to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn description(&self) -> String {
+        self.to_possible_value()
+            .and_then(|value| value.get_help().map(|help| help.to_string()))
+            .unwrap_or_default()
+    }
+
+    pub fn category(&self) -> Category {
+        match self {
+            Self::Quit | Self::Help | Self::CommandLine => Category::General,
+            Self::Down
+            | Self::Up
+            | Self::PageDown
+            | Self::PageUp
+            | Self::HalfPageDown
+            | Self::HalfPageUp
+            | Self::FirstRow
+            | Self::LastRow
+            | Self::NextBucket
+            | Self::PreviousBucket
+            | Self::ToggleTimeline => Category::Navigation,
+            Self::Grep | Self::PivotOnColumn | Self::PivotOnValue | Self::Back => Category::Search,
+            Self::ScrollRight
+            | Self::ScrollLeft
+            | Self::NextColumn
+            | Self::PreviousColumn
+            | Self::ColumnStats
+            | Self::ToggleTimestamps
+            | Self::ToggleWrap => Category::Columns,
+            Self::ToggleRawLine
+            | Self::ToggleRawView
+            | Self::HexDump
+            | Self::ToggleMaskUnicode
+            | Self::ShowSuspicious => Category::Inspect,
+            Self::NextTab
+            | Self::PreviousTab
+            | Self::SelectTab
+            | Self::SwitchPane
+            | Self::ToggleSync => Category::Tabs,
+            Self::ToggleBookmark
+            | Self::ShowBookmarks
+            | Self::SetMark
+            | Self::JumpToMark
+            | Self::NextBookmark
+            | Self::PreviousBookmark
+            | Self::ToggleSelection
+            | Self::ClearSelection
+            | Self::EditNote => Category::Marks,
+            Self::YankRow | Self::YankCell | Self::YankSelection => Category::Export,
+        }
+    }
+
+    /// Returns `true` if the action needs another key as argument, such as
+    /// the name of a mark
+    pub fn takes_argument(&self) -> bool {
+        matches!(self, Self::SetMark | Self::JumpToMark)
+    }
+}
@@ -0,0 +1,259 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use getset::Getters;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{LogData, LogLine};
+
+/// extension which is appended to the name of the input file to get the
+/// name of the sidecar file
+const SIDECAR_EXTENSION: &str = "loghawk.json";
+
+/// Note and tags which an analyst has attached to a record
+#[derive(Clone, Debug, Default, Getters, Serialize, Deserialize)]
+#[getset(get = "pub")]
+pub struct Annotation {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    note: String,
+
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    tags: BTreeSet<String>,
+}
+
+impl Annotation {
+    pub fn is_empty(&self) -> bool {
+        self.note.is_empty() && self.tags.is_empty()
+    }
+}
+
+/// An annotation as it is stored in the sidecar file. The record is found by
+/// its byte offset in the file, and the hash is used to check that the
+/// record at this offset still has the same contents.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct StoredAnnotation {
+    /// index of the record, which is only used if the offset is unknown
+    record: usize,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    offset: Option<u64>,
+    hash: String,
+
+    #[serde(flatten)]
+    annotation: Annotation,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Sidecar {
+    annotations: Vec<StoredAnnotation>,
+}
+
+/// Annotations of all records of a file, which are persisted in a sidecar
+/// file next to it
+#[derive(Debug, Default)]
+pub struct Annotations {
+    /// sidecar file, or `None` if the input cannot have one (e.g. stdin)
+    sidecar: Option<PathBuf>,
+
+    annotations: BTreeMap<usize, Annotation>,
+
+    /// annotations from the sidecar file which do not match any record; they
+    /// are kept so that they are not lost when the sidecar file is written
+    orphaned: Vec<StoredAnnotation>,
+
+    /// the reason why the sidecar file could not be read; it is not
+    /// overwritten then, so that it can be repaired
+    load_error: Option<String>,
+}
+
+impl Annotations {
+    /// Reads the annotations of the file at `path`, if there are any
+    pub fn load(path: &Path, data: &dyn LogData) -> anyhow::Result<Self> {
+        let sidecar = sidecar_path(path);
+        let mut me = Self {
+            sidecar: Some(sidecar.clone()),
+            ..Default::default()
+        };
+        if !sidecar.exists() {
+            return Ok(me);
+        }
+
+        let reader = BufReader::new(
+            File::open(&sidecar)
+                .with_context(|| format!("unable to read {}", sidecar.display()))?,
+        );
+        let stored: Sidecar = serde_json::from_reader(reader)
+            .with_context(|| format!("invalid annotations in {}", sidecar.display()))?;
+
+        let records_by_offset: HashMap<u64, usize> = (0..data.len())
+            .filter_map(|idx| Some((*data.line(idx)?.position()?.offset(), idx)))
+            .collect();
+        // the records have no positions if invalid bytes have been replaced
+        // while decoding the input, so their index is used instead
+        let offsets_known = !data.raw_input().is_lossy();
+        for stored in stored.annotations {
+            // an annotation is only attached to the record at the same offset
+            // which still has the same contents, never to another record
+            let record = match stored.offset.filter(|_| offsets_known) {
+                Some(offset) => records_by_offset.get(&offset).copied(),
+                None => Some(stored.record),
+            }
+            .filter(|record| record_hash(data, *record).as_ref() == Some(&stored.hash));
+            match record {
+                Some(record) => {
+                    me.annotations.insert(record, stored.annotation);
+                }
+                None => me.orphaned.push(stored),
+            }
+        }
+        Ok(me)
+    }
+
+    /// Creates empty annotations for a file whose sidecar file could not be
+    /// read. They are never saved, so that the sidecar file is not lost.
+    pub fn unreadable(path: &Path, why: &anyhow::Error) -> Self {
+        Self {
+            sidecar: Some(sidecar_path(path)),
+            load_error: Some(format!("{why:#}")),
+            ..Default::default()
+        }
+    }
+
+    /// the reason why the sidecar file could not be read
+    pub fn load_error(&self) -> Option<&str> {
+        self.load_error.as_deref()
+    }
+
+    pub fn get(&self, record: usize) -> Option<&Annotation> {
+        self.annotations.get(&record)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.annotations.is_empty()
+    }
+
+    /// Replaces the note of a record. An empty note removes it.
+    pub fn set_note(&mut self, record: usize, note: String) {
+        self.annotations.entry(record).or_default().note = note;
+        self.remove_if_empty(record);
+    }
+
+    pub fn add_tag(&mut self, record: usize, tag: String) {
+        self.annotations.entry(record).or_default().tags.insert(tag);
+    }
+
+    /// Removes a tag from a record. Returns `false` if the record did not
+    /// have this tag.
+    pub fn remove_tag(&mut self, record: usize, tag: &str) -> bool {
+        let removed = self
+            .annotations
+            .get_mut(&record)
+            .map(|annotation| annotation.tags.remove(tag))
+            .unwrap_or(false);
+        self.remove_if_empty(record);
+        removed
+    }
+
+    /// All tags which are used by any record
+    pub fn tags(&self) -> BTreeSet<&str> {
+        self.annotations
+            .values()
+            .flat_map(|annotation| annotation.tags.iter().map(String::as_str))
+            .collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&usize, &Annotation)> {
+        self.annotations.iter()
+    }
+
+    /// Writes all annotations to the sidecar file, or removes the file if
+    /// there are none
+    pub fn save(&self, data: &dyn LogData) -> anyhow::Result<()> {
+        let sidecar = match &self.sidecar {
+            Some(sidecar) => sidecar,
+            None => anyhow::bail!("annotations cannot be stored for this input"),
+        };
+        if self.load_error.is_some() {
+            anyhow::bail!(
+                "the annotations are not saved, because {} could not be read",
+                sidecar.display()
+            );
+        }
+
+        let mut stored = Sidecar::default();
+        for (record, annotation) in self.annotations.iter() {
+            if let Some(hash) = record_hash(data, *record) {
+                stored.annotations.push(StoredAnnotation {
+                    record: *record,
+                    offset: data
+                        .line(*record)
+                        .and_then(LogLine::position)
+                        .map(|position| *position.offset()),
+                    hash,
+                    annotation: annotation.clone(),
+                });
+            }
+        }
+        stored.annotations.extend(self.orphaned.iter().cloned());
+
+        if stored.annotations.is_empty() {
+            if sidecar.exists() {
+                std::fs::remove_file(sidecar)?;
+            }
+            return Ok(());
+        }
+
+        let writer = BufWriter::new(
+            File::create(sidecar)
+                .with_context(|| format!("unable to write {}", sidecar.display()))?,
+        );
+        serde_json::to_writer_pretty(writer, &stored)?;
+        Ok(())
+    }
+
+    fn remove_if_empty(&mut self, record: usize) {
+        if self
+            .annotations
+            .get(&record)
+            .is_some_and(Annotation::is_empty)
+        {
+            self.annotations.remove(&record);
+        }
+    }
+}
+
+/// Returns the path of the sidecar file, e.g. `auth.csv.loghawk.json` for
+/// `auth.csv`
+pub fn sidecar_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".");
+    file_name.push(SIDECAR_EXTENSION);
+    path.with_file_name(file_name)
+}
+
+/// SHA-256 hash of the contents of a record, as hex string
+fn record_hash(data: &dyn LogData, record: usize) -> Option<String> {
+    data.line(record).map(line_hash)
+}
+
+fn line_hash(line: &LogLine) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(line.key_value().as_bytes());
+    for value in line.iter_contents() {
+        // separate the values, so that moving characters between columns
+        // changes the hash
+        hasher.update([0x1f]);
+        hasher.update(value.as_bytes());
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
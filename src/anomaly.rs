@@ -0,0 +1,217 @@
+use std::fmt::Display;
+
+use chrono::TimeDelta;
+use getset::Getters;
+
+use crate::{LogData, LogLine};
+
+/// Kinds of suspicious patterns in time-ordered logs
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AnomalyKind {
+    /// there is a gap larger than the threshold before this row
+    Gap(TimeDelta),
+
+    /// the timestamp of this row is lower than the one of the previous row
+    OutOfOrder(TimeDelta),
+
+    /// this row is identical to the previous row
+    Duplicate,
+}
+
+impl Display for AnomalyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Gap(delta) => write!(f, "gap of {}", format_duration(delta)),
+            Self::OutOfOrder(delta) => {
+                write!(f, "out of order, {} earlier", format_duration(delta))
+            }
+            Self::Duplicate => write!(f, "duplicate row"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Getters)]
+#[getset(get = "pub")]
+pub struct Anomaly {
+    /// index of the record where the anomaly has been found
+    record: usize,
+    kind: AnomalyKind,
+}
+
+/// Walks through the given rows and reports gaps larger than `threshold`,
+/// timestamps going backwards and duplicate consecutive rows
+pub fn detect_anomalies(data: &dyn LogData, rows: &[usize], threshold: TimeDelta) -> Vec<Anomaly> {
+    let mut anomalies = Vec::new();
+    let mut last_timestamp = None;
+    let mut previous: Option<&LogLine> = None;
+
+    for current in rows {
+        let line = match data.line(*current) {
+            Some(line) => line,
+            None => continue,
+        };
+
+        if let Some(previous) = previous.replace(line) {
+            if previous.key_value() == line.key_value()
+                && previous.iter_contents().eq(line.iter_contents())
+            {
+                anomalies.push(Anomaly {
+                    record: *current,
+                    kind: AnomalyKind::Duplicate,
+                });
+            }
+        }
+
+        if let Some(timestamp) = line.timestamp() {
+            if let Some(last_timestamp) = last_timestamp {
+                let delta: TimeDelta = *timestamp - last_timestamp;
+                if delta < TimeDelta::zero() {
+                    anomalies.push(Anomaly {
+                        record: *current,
+                        kind: AnomalyKind::OutOfOrder(-delta),
+                    });
+                } else if delta > threshold {
+                    anomalies.push(Anomaly {
+                        record: *current,
+                        kind: AnomalyKind::Gap(delta),
+                    });
+                }
+            }
+            last_timestamp = Some(*timestamp);
+        }
+    }
+    anomalies
+}
+
+/// Parses durations like `90s`, `5m`, `1h` or `2d`. Numbers without unit
+/// are interpreted as seconds.
+pub fn parse_duration(value: &str) -> anyhow::Result<TimeDelta> {
+    let value = value.trim();
+    let unit_start = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(unit_start);
+    let amount: i64 = amount.parse()?;
+    let delta = match unit {
+        "" | "s" => TimeDelta::try_seconds(amount),
+        "m" => TimeDelta::try_minutes(amount),
+        "h" => TimeDelta::try_hours(amount),
+        "d" => TimeDelta::try_days(amount),
+        _ => anyhow::bail!("invalid duration unit: '{unit}', use one of s, m, h or d"),
+    };
+    delta.ok_or_else(|| anyhow::anyhow!("duration is too large: {value}"))
+}
+
+/// Formats a duration using its two most significant units, e.g. `2h 50m`
+pub fn format_duration(delta: &TimeDelta) -> String {
+    let seconds = delta.num_seconds();
+    if seconds == 0 {
+        return format!("{}ms", delta.num_milliseconds());
+    }
+
+    let parts = [
+        (seconds / 86400, "d"),
+        ((seconds % 86400) / 3600, "h"),
+        ((seconds % 3600) / 60, "m"),
+        (seconds % 60, "s"),
+    ];
+    parts
+        .iter()
+        .skip_while(|(amount, _)| *amount == 0)
+        .take(2)
+        .filter(|(amount, _)| *amount != 0)
+        .map(|(amount, unit)| format!("{amount}{unit}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use chrono::TimeDelta;
+    use clio::ClioPath;
+
+    use super::{detect_anomalies, format_duration, parse_duration, AnomalyKind};
+    use crate::CsvData;
+
+    fn csv(text: &str) -> CsvData {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+        CsvData::try_from(&ClioPath::new(file.path()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_detect_anomalies() {
+        let data = csv(concat!(
+            "time,message\n",
+            "2024-03-01T10:00:00Z,a\n",
+            "2024-03-01T10:30:00Z,b\n",
+            "2024-03-01T12:00:00Z,c\n",
+            "2024-03-01T11:55:00Z,d\n",
+            "2024-03-01T11:55:00Z,d\n",
+            "no timestamp,e\n",
+            "2024-03-01T11:56:00Z,f\n",
+        ));
+        let rows: Vec<_> = (0..7).collect();
+        let anomalies: Vec<_> = detect_anomalies(&data, &rows, TimeDelta::hours(1))
+            .into_iter()
+            .map(|anomaly| (*anomaly.record(), anomaly.kind().clone()))
+            .collect();
+        assert_eq!(
+            anomalies,
+            [
+                (2, AnomalyKind::Gap(TimeDelta::minutes(90))),
+                (3, AnomalyKind::OutOfOrder(TimeDelta::minutes(5))),
+                (4, AnomalyKind::Duplicate),
+            ]
+        );
+
+        // only the given rows are compared with each other
+        let anomalies = detect_anomalies(&data, &[0, 3, 6], TimeDelta::hours(1));
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(
+            anomalies[0].kind(),
+            &AnomalyKind::Gap(TimeDelta::minutes(115))
+        );
+
+        let anomalies = detect_anomalies(&data, &[0, 1, 2], TimeDelta::hours(2));
+        assert!(anomalies.is_empty());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        let cases = [
+            ("90", TimeDelta::seconds(90)),
+            ("90s", TimeDelta::seconds(90)),
+            (" 5m ", TimeDelta::minutes(5)),
+            ("1h", TimeDelta::hours(1)),
+            ("2d", TimeDelta::days(2)),
+            ("0s", TimeDelta::zero()),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(parse_duration(value).unwrap(), expected, "{value}");
+        }
+
+        for invalid in ["", "h", "-5m", "1.5h", "5 m", "3w"] {
+            assert!(parse_duration(invalid).is_err(), "{invalid}");
+        }
+
+        // too large for the number, and too large for the duration
+        assert!(parse_duration("99999999999999999999s").is_err());
+        let why = parse_duration("9999999999999d").unwrap_err();
+        assert_eq!(why.to_string(), "duration is too large: 9999999999999d");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(&TimeDelta::milliseconds(250)), "250ms");
+        assert_eq!(format_duration(&TimeDelta::seconds(90)), "1m 30s");
+        assert_eq!(format_duration(&TimeDelta::minutes(170)), "2h 50m");
+        assert_eq!(format_duration(&TimeDelta::hours(48)), "2d");
+        assert_eq!(
+            format_duration(&(TimeDelta::days(1) + TimeDelta::seconds(5))),
+            "1d"
+        );
+    }
+}
@@ -1,13 +1,33 @@
-use std::error;
+use std::{
+    error,
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{Receiver, TryRecvError},
+        Arc,
+    },
+};
 
+use anyhow::Context;
+use chrono::TimeDelta;
+use clap::ValueEnum;
+use clio::ClioPath;
 use getset::{Getters, Setters};
-use ratatui::{layout::Rect, Frame};
+use ratatui::{layout::Rect, style::Stylize, widgets::Paragraph, Frame};
 
 use crate::{
-    cli::{Cli, FileFormat},
-    csv_data::CsvData,
+    cli::Cli,
+    column_stats::ColumnStats,
+    command_line::{CommandLine, History},
+    commands, copy_to_clipboard, detect_anomalies, escape_line_breaks, exceeds_clipboard_limit,
+    export, find_suspicious_values, format_duration, hex_dump,
     log_view::LogView,
-    LogData, LogViewState, TxtData, ViewPort,
+    sort_rows,
+    tab::Tab,
+    Action, Annotations, ClipboardTarget, Config, ExportFormat, ExportScope, Key, Keymap,
+    LogViewState, Overlay, OverlayAction, OverlayItem, RawView, RowFilter, Session, SplitLayout,
+    TimeRange, TimelineView, TimestampDisplay, ViewPort, WrapLayout, YankFormat,
 };
 
 /// Application result type.
@@ -22,9 +42,70 @@ pub struct App {
 
     cli: Cli,
 
-    data: Box<dyn LogData>,
+    tabs: Vec<Tab>,
+
+    active_tab: usize,
+
+    /// tab which is displayed in the pane without focus
+    secondary_tab: usize,
+
+    split_layout: SplitLayout,
+
+    /// display the secondary pane at the left or top position
+    secondary_first: bool,
+
+    /// keep the secondary pane at the same time as the active pane
+    sync_by_time: bool,
+
+    timestamp_display: TimestampDisplay,
+
+    /// rows which are displayed as they have been found in the input
+    raw_view: RawView,
+
+    /// display long rows over multiple lines
+    wrap: bool,
+
+    /// display the timeline above the log view
+    show_timeline: bool,
+
+    /// area where the timeline has been rendered, used to handle mouse clicks
+    timeline_area: Option<Rect>,
+
+    /// popup which is displayed above the log view
+    overlay: Option<Overlay>,
+
+    /// column statistics which are being computed in the background
+    pending_stats: Option<Receiver<ColumnStats>>,
+
+    command_line: Option<CommandLine>,
+
+    message: Option<String>,
+
+    /// error which is displayed until the next key is pressed
+    error: Option<String>,
+
+    /// format of rows which are copied to the clipboard
+    yank_format: YankFormat,
 
-    viewstate: LogViewState,
+    #[getset(set)]
+    keymap: Keymap,
+
+    /// keys of a key sequence like `]b`, which is waiting for more keys
+    pending_keys: Vec<Key>,
+
+    /// count prefix which has been typed before an action, e.g. `50` in
+    /// `50j`
+    count: Option<usize>,
+
+    /// action which is waiting for its argument, e.g. the name of a mark
+    pending_action: Option<Action>,
+
+    /// display unicode characters which are not ASCII as escape sequences
+    mask_unicode: bool,
+
+    /// commands which have been entered on the command line
+    #[getset(skip)]
+    history: History,
 
     page_size: u16,
 }
@@ -32,82 +113,1066 @@ pub struct App {
 impl App {
     /// Constructs a new instance of [`App`].
     pub fn new(cli: Cli) -> anyhow::Result<Self> {
-        let data: Box<dyn LogData> = match cli.file_format() {
-            FileFormat::Csv => Box::new(CsvData::try_from(cli.file().path())?),
-            FileFormat::Txt => Box::new(TxtData::load_from(cli.file().path(), *cli.delimiter())?),
-        };
+        let mut tab = Self::load_tab(&cli, cli.file().path())?;
+        Self::apply_cli_filters(&cli, &mut tab)?;
+        let mask_unicode = *cli.mask_unicode();
+        let wrap = *cli.wrap();
+        let keymap = Config::load()?.keys().keymap(*cli.keymap())?;
 
-        let viewstate = LogViewState::default();
-        Ok(Self {
+        let mut app = Self {
             running: true,
             cli,
-            data,
-            viewstate,
+            tabs: vec![tab],
+            active_tab: 0,
+            secondary_tab: 0,
+            split_layout: SplitLayout::default(),
+            secondary_first: false,
+            sync_by_time: false,
+            timestamp_display: TimestampDisplay::default(),
+            raw_view: RawView::default(),
+            wrap,
+            show_timeline: false,
+            timeline_area: None,
+            overlay: None,
+            pending_stats: None,
+            command_line: None,
+            message: None,
+            error: None,
+            yank_format: YankFormat::default(),
+            keymap,
+            pending_keys: Vec::new(),
+            count: None,
+            pending_action: None,
+            mask_unicode,
+            history: History::load(),
             page_size: 1,
-        })
+        };
+        app.report_load_problems();
+        Ok(app)
     }
 
     /// Handles the tick event of the terminal.
-    pub fn tick(&self) {}
+    pub fn tick(&mut self) {
+        if let Some(receiver) = &self.pending_stats {
+            match receiver.try_recv() {
+                Ok(stats) => {
+                    self.pending_stats = None;
+                    self.message = None;
+                    self.show_column_stats(stats);
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => {
+                    self.pending_stats = None;
+                    self.message = None;
+                    self.show_error("failed to compute column statistics");
+                }
+            }
+        }
+    }
+
+    /// Displays an error in the status bar until the next key is pressed.
+    /// For [`anyhow::Error`], the causes are displayed as well.
+    pub fn show_error(&mut self, why: impl std::fmt::Display) {
+        self.error = Some(format!("{why:#}"));
+    }
 
     /// Set running to false to quit the application.
     pub fn quit(&mut self) {
         self.running = false;
     }
 
+    pub fn tab(&self) -> &Tab {
+        &self.tabs[self.active_tab]
+    }
+
+    pub fn tab_mut(&mut self) -> &mut Tab {
+        &mut self.tabs[self.active_tab]
+    }
+
+    /// Loads a file and restores its session
+    fn load_tab(cli: &Cli, path: &ClioPath) -> anyhow::Result<Tab> {
+        let mut tab = Tab::load_from(path, cli.file_format(), *cli.delimiter())?;
+        if !cli.no_session() && !cli.is_batch() && !path.is_std() {
+            // a session which cannot be read is not worth to fail for
+            if let Ok(Some(session)) = Session::load(path.path()) {
+                tab.restore_session(&session);
+            }
+        }
+        Ok(tab)
+    }
+
+    /// Applies the filters and the time range which have been specified on
+    /// the command line. Filters which have already been restored from the
+    /// session are not added again.
+    fn apply_cli_filters(cli: &Cli, tab: &mut Tab) -> anyhow::Result<()> {
+        let mut filters = Vec::new();
+        for filter in cli.filters() {
+            let (column, value) = filter
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("invalid filter '{filter}', use COLUMN=VALUE"))?;
+            let column_name = column.trim().to_string();
+            let column = tab
+                .data()
+                .find_column(&column_name)
+                .ok_or_else(|| anyhow::anyhow!("unknown column: {column_name}"))?;
+            filters.push(RowFilter::ColumnEquals {
+                column,
+                column_name,
+                value: value.to_string(),
+            });
+        }
+        filters.extend(cli.grep().iter().cloned().map(RowFilter::Contains));
+        filters.extend(cli.tags().iter().cloned().map(RowFilter::Tag));
+        for filter in filters {
+            if !tab.filters().contains(&filter) {
+                tab.add_filter(filter);
+            }
+        }
+        if cli.from().is_some() || cli.to().is_some() {
+            tab.set_time_range(Some(TimeRange::new(
+                *cli.from(),
+                *cli.to(),
+                *cli.keep_unparsed(),
+            )));
+        }
+        Ok(())
+    }
+
+    /// Writes rows of the current tab to a file, or to stdout if `path` is
+    /// `-`. Returns the number of exported rows.
+    pub fn export(
+        &self,
+        path: &Path,
+        format: ExportFormat,
+        scope: ExportScope,
+    ) -> anyhow::Result<usize> {
+        let rows = scope.rows(self.tab());
+        let columns: Vec<_> = (0..self.tab().data().column_count()).collect();
+        self.write_rows(path, format, &rows, &columns)?;
+        Ok(rows.len())
+    }
+
+    /// Writes the rows which match the filters without displaying them, using
+    /// the columns, sort order and target which are given on the command line
+    pub fn print(&self) -> anyhow::Result<()> {
+        let data = self.tab().data();
+        let find_column = |name: &String| {
+            data.find_column(name)
+                .ok_or_else(|| anyhow::anyhow!("unknown column: {name}"))
+        };
+
+        let columns = if self.cli.columns().is_empty() {
+            (0..data.column_count()).collect()
+        } else {
+            self.cli
+                .columns()
+                .iter()
+                .map(find_column)
+                .collect::<anyhow::Result<Vec<_>>>()?
+        };
+
+        let mut rows = self.tab().visible_rows().clone();
+        if let Some(column) = self.cli.sort() {
+            sort_rows(data.as_ref(), &mut rows, find_column(column)?);
+        }
+        if *self.cli.reverse() {
+            rows.reverse();
+        }
+
+        let path = self
+            .cli
+            .export()
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("-"));
+        let format = self
+            .cli
+            .export_format()
+            .or_else(|| ExportFormat::from_path(&path))
+            .unwrap_or(if self.cli.export().is_some() {
+                ExportFormat::Csv
+            } else {
+                ExportFormat::Text
+            });
+        self.write_rows(&path, format, &rows, &columns)
+    }
+
+    fn write_rows(
+        &self,
+        path: &Path,
+        format: ExportFormat,
+        rows: &[usize],
+        columns: &[usize],
+    ) -> anyhow::Result<()> {
+        if path == Path::new("-") {
+            export(self.tab(), rows, columns, format, std::io::stdout().lock())
+        } else {
+            let file = File::create(path)
+                .with_context(|| format!("unable to create {}", path.display()))?;
+            export(self.tab(), rows, columns, format, BufWriter::new(file))
+        }
+    }
+
+    /// Saves the state of all tabs, so that it can be restored when the
+    /// files are opened again
+    pub fn save_sessions(&self) -> anyhow::Result<()> {
+        if *self.cli.no_session() {
+            return Ok(());
+        }
+        for tab in self.tabs.iter() {
+            if let Some(session) = tab.session()? {
+                session.save()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Opens another file in a new tab and activates it
+    pub fn open(&mut self, path: &str) -> anyhow::Result<()> {
+        let path = ClioPath::new(path)?;
+        let tab = Self::load_tab(&self.cli, &path)
+            .with_context(|| format!("unable to open {}", path.display()))?;
+        self.tabs.push(tab);
+        self.activate_tab(self.tabs.len() - 1);
+        self.report_load_problems();
+        Ok(())
+    }
+
+    pub fn select_tab(&mut self, idx: usize) {
+        if idx < self.tabs.len() {
+            self.activate_tab(idx);
+        }
+    }
+
+    pub fn next_tab(&mut self) {
+        self.activate_tab((self.active_tab + 1) % self.tabs.len());
+    }
+
+    pub fn previous_tab(&mut self) {
+        self.activate_tab((self.active_tab + self.tabs.len() - 1) % self.tabs.len());
+    }
+
+    /// Activates a tab. If the tab is already displayed in the secondary
+    /// pane, both panes swap their contents.
+    fn activate_tab(&mut self, idx: usize) {
+        if idx == self.secondary_tab {
+            self.secondary_tab = self.active_tab;
+        }
+        self.active_tab = idx;
+    }
+
+    /// Splits the main area into two panes, displaying the active tab and
+    /// another tab
+    pub fn split(&mut self, layout: SplitLayout) -> anyhow::Result<()> {
+        if layout != SplitLayout::Single {
+            if self.tabs.len() < 2 {
+                anyhow::bail!("a split view needs at least two open files");
+            }
+            if self.secondary_tab == self.active_tab {
+                self.secondary_tab = (self.active_tab + 1) % self.tabs.len();
+            }
+        }
+        self.split_layout = layout;
+        self.sync_secondary_pane();
+        Ok(())
+    }
+
+    /// Moves the focus to the other pane
+    pub fn switch_pane(&mut self) {
+        if self.split_layout != SplitLayout::Single {
+            std::mem::swap(&mut self.active_tab, &mut self.secondary_tab);
+            self.secondary_first = !self.secondary_first;
+        }
+    }
+
+    /// Switches between the original index values, UTC and the configured
+    /// timezone
+    pub fn toggle_timestamp_display(&mut self) {
+        self.timestamp_display = self.timestamp_display.next(*self.cli.timezone());
+    }
+
+    /// Switches between the parsed rows and the raw view, or from one raw
+    /// view to the other
+    pub fn toggle_raw_view(&mut self, raw_view: RawView) {
+        self.raw_view = if self.raw_view == raw_view {
+            RawView::Off
+        } else {
+            raw_view
+        };
+    }
+
+    /// Reads the bytes of the current row from the input and displays them
+    /// as hex dump
+    pub fn show_hex_dump(&mut self) {
+        let Some(record) = self.tab().cursor_record() else {
+            return;
+        };
+        let data = self.tab().data();
+        let offset = data
+            .line(record)
+            .and_then(|line| line.position())
+            .map(|position| *position.offset())
+            .unwrap_or_default();
+        match data.raw_bytes(record) {
+            Ok(bytes) => {
+                let items = hex_dump(&bytes, offset)
+                    .into_iter()
+                    .map(|line| OverlayItem::new(line, OverlayAction::None))
+                    .collect();
+                let title = format!("hex dump of row {} ({} bytes)", record + 1, bytes.len());
+                self.overlay = Some(Overlay::new(title, items));
+            }
+            Err(why) => self.show_error(why),
+        }
+    }
+
+    /// Searches the visible rows for gaps, out-of-order timestamps and
+    /// duplicates, marks them in the gutter and displays the findings
+    pub fn find_anomalies(&mut self, threshold: TimeDelta) {
+        let tab = self.tab_mut();
+        let anomalies = detect_anomalies(tab.data().as_ref(), tab.visible_rows(), threshold);
+        let items = anomalies
+            .iter()
+            .filter_map(|anomaly| {
+                tab.data().line(*anomaly.record()).map(|line| {
+                    OverlayItem::new(
+                        format!("{}: {}", line.key_value(), anomaly.kind()),
+                        OverlayAction::JumpToRecord(*anomaly.record()),
+                    )
+                })
+            })
+            .collect();
+        tab.set_anomalies(anomalies);
+        self.overlay = Some(Overlay::new(
+            format!(
+                "anomalies (gaps larger than {})",
+                format_duration(&threshold)
+            ),
+            items,
+        ));
+    }
+
+    /// Lists all values, including those of rows which are hidden by filters,
+    /// which contain characters that are used to hide or disguise text
+    pub fn show_suspicious_values(&mut self) {
+        let data = self.tab().data();
+        let values = find_suspicious_values(data.as_ref());
+        let items: Vec<_> = values
+            .iter()
+            .filter_map(|value| {
+                let line = data.line(*value.record())?;
+                let column = data.column_info(*value.column())?;
+                Some(OverlayItem::new(
+                    format!(
+                        "{} [{}]: {}",
+                        line.key_value(),
+                        column.name(),
+                        value.describe()
+                    ),
+                    OverlayAction::JumpToCell {
+                        record: *value.record(),
+                        column: *value.column(),
+                    },
+                ))
+            })
+            .collect();
+        if items.is_empty() {
+            self.message = Some("found no suspicious characters".into());
+        } else {
+            self.overlay = Some(Overlay::new("suspicious characters".into(), items));
+        }
+    }
+
+    /// The focused column
+    pub fn current_column(&self) -> usize {
+        *self.tab().viewstate().column()
+    }
+
+    /// Starts computing statistics of a column on a background thread
+    pub fn compute_column_stats(&mut self, column: usize) {
+        let tab = self.tab();
+        self.pending_stats = Some(ColumnStats::spawn(
+            Arc::clone(tab.data()),
+            tab.visible_rows().clone(),
+            column,
+        ));
+        self.message = Some("computing column statistics ...".into());
+    }
+
+    fn show_column_stats(&mut self, stats: ColumnStats) {
+        let filter_item = |(value, count): &(String, usize)| {
+            OverlayItem::new(
+                format!(
+                    "{count:>10} {:>6.2}%  {}",
+                    stats.percentage(*count),
+                    if value.is_empty() { "<empty>" } else { value }
+                ),
+                OverlayAction::Filter(RowFilter::ColumnEquals {
+                    column: *stats.column(),
+                    column_name: stats.column_name().clone(),
+                    value: value.clone(),
+                }),
+            )
+        };
+        let label = |text: &str| OverlayItem::new(text.to_string(), OverlayAction::None);
+
+        let items = std::iter::once(label("most frequent values:"))
+            .chain(stats.top().iter().map(filter_item))
+            .chain(std::iter::once(label("least frequent values:")))
+            .chain(stats.bottom().iter().map(filter_item))
+            .collect();
+
+        let header = vec![
+            format!(
+                "rows: {}, distinct: {}, empty: {}",
+                stats.rows(),
+                stats.distinct(),
+                stats.empty()
+            ),
+            format!(
+                "min: {}, max: {} ({:?})",
+                stats.min().as_deref().unwrap_or("-"),
+                stats.max().as_deref().unwrap_or("-"),
+                stats.kind()
+            ),
+            String::new(),
+        ];
+
+        self.overlay = Some(
+            Overlay::new(format!("statistics of '{}'", stats.column_name()), items)
+                .with_header(header),
+        );
+    }
+
+    pub fn close_overlay(&mut self) {
+        self.overlay = None;
+    }
+
+    pub fn overlay_mut(&mut self) -> Option<&mut Overlay> {
+        self.overlay.as_mut()
+    }
+
+    /// Executes the action of the selected overlay item
+    pub fn submit_overlay(&mut self) {
+        let action = self
+            .overlay
+            .as_ref()
+            .and_then(|overlay| overlay.selected_item())
+            .map(|item| item.action().clone());
+        match action {
+            Some(OverlayAction::Filter(filter)) => self.add_filter(filter),
+            Some(OverlayAction::None) | None => (),
+            Some(OverlayAction::JumpToRecord(record)) => self.jump_to_record(record),
+            Some(OverlayAction::JumpToCell { record, column }) => {
+                self.jump_to_record(record);
+                self.focus_column(column);
+            }
+        }
+    }
+
+    /// Moves the cursor to the record, if it is not hidden by a filter
+    fn jump_to_record(&mut self, record: usize) {
+        if self.tab_mut().scroll_to_record(record) {
+            self.sync_secondary_pane();
+        } else {
+            self.message = Some("this row is not visible".into());
+        }
+    }
+
+    pub fn toggle_timeline(&mut self) {
+        self.show_timeline = !self.show_timeline;
+    }
+
+    /// Jumps to the first row in the next non-empty bucket of the timeline
+    pub fn next_bucket(&mut self) {
+        if let Some(bucket) =
+            self.find_bucket(|current, counts| (current + 1..counts.len()).find(|b| counts[*b] > 0))
+        {
+            self.jump_to_bucket(bucket);
+        }
+    }
+
+    /// Jumps to the first row in the previous non-empty bucket of the timeline
+    pub fn previous_bucket(&mut self) {
+        if let Some(bucket) =
+            self.find_bucket(|current, counts| (0..current).rev().find(|b| counts[*b] > 0))
+        {
+            self.jump_to_bucket(bucket);
+        }
+    }
+
+    fn find_bucket(&self, f: impl Fn(usize, &[u64]) -> Option<usize>) -> Option<usize> {
+        let tab = self.tab();
+        let timeline = tab.cached_timeline()?;
+        f(tab.current_bucket().unwrap_or_default(), timeline.counts())
+    }
+
+    pub fn jump_to_bucket(&mut self, bucket: usize) {
+        if let Some(row) = self.tab().first_row_in_bucket(bucket) {
+            self.set_cursor(row);
+        }
+    }
+
+    /// Handles a mouse click at the given screen position
+    pub fn click(&mut self, column: u16, row: u16) {
+        if let Some(area) = self.timeline_area {
+            if area.contains(ratatui::layout::Position { x: column, y: row }) {
+                self.jump_to_bucket(usize::from(column - area.x));
+            }
+        }
+    }
+
+    pub fn toggle_sync_by_time(&mut self) {
+        self.sync_by_time = !self.sync_by_time;
+        self.sync_secondary_pane();
+    }
+
+    /// Moves the cursor of the secondary pane to the row whose timestamp is
+    /// nearest to the timestamp of the cursor row in the active pane. Rows
+    /// without timestamp are skipped, and the rows do not need to be sorted.
+    /// If the cursor row has no timestamp, the cursor is moved to the row with
+    /// the largest index value which is not larger than that of the cursor
+    /// row.
+    fn sync_secondary_pane(&mut self) {
+        if !self.sync_by_time || self.split_layout == SplitLayout::Single {
+            return;
+        }
+
+        let active = self.tab();
+        let (key, timestamp) = match active
+            .cursor_record()
+            .and_then(|record| active.data().line(record))
+        {
+            Some(line) => (line.key_value().to_owned(), line.timestamp().cloned()),
+            None => return,
+        };
+
+        let secondary = &mut self.tabs[self.secondary_tab];
+        let data = secondary.data();
+        let lines = secondary
+            .visible_rows()
+            .iter()
+            .enumerate()
+            .filter_map(|(position, idx)| Some((position, data.line(*idx)?)));
+        // the first of several equally near rows is taken
+        let position = match timestamp {
+            Some(timestamp) => lines
+                .filter_map(|(position, line)| {
+                    Some((position, (*line.timestamp()? - timestamp).abs()))
+                })
+                .min_by_key(|(position, distance)| (*distance, *position))
+                .map(|(position, _)| position),
+            None => lines
+                .filter(|(_, line)| line.key_value() <= key.as_str())
+                .max_by(|(p1, l1), (p2, l2)| l1.key_value().cmp(l2.key_value()).then(p2.cmp(p1)))
+                .map(|(position, _)| position),
+        };
+        if let Some(position) = position {
+            secondary.viewstate_mut().set_cursor(position);
+        }
+    }
+
+    pub fn open_command_line(&mut self) {
+        self.open_command_line_with(String::new());
+    }
+
+    /// Opens the command line with some text already entered
+    pub fn open_command_line_with(&mut self, input: String) {
+        self.message = None;
+        self.command_line = Some(CommandLine::with_input(input));
+    }
+
+    /// Opens the command line with the note of the current row, so that it
+    /// can be edited
+    pub fn edit_note(&mut self) {
+        let note = self
+            .tab()
+            .cursor_record()
+            .and_then(|record| self.tab().annotation(record))
+            .map(|annotation| annotation.note().clone())
+            .unwrap_or_default();
+        self.open_command_line_with(format!("note {note}"));
+    }
+
+    pub fn close_command_line(&mut self) {
+        self.command_line = None;
+    }
+
+    pub fn command_line_mut(&mut self) -> Option<&mut CommandLine> {
+        self.command_line.as_mut()
+    }
+
+    pub fn history_previous(&mut self) {
+        if let Some(command_line) = self.command_line.as_mut() {
+            command_line.history_previous(&self.history);
+        }
+    }
+
+    pub fn history_next(&mut self) {
+        if let Some(command_line) = self.command_line.as_mut() {
+            command_line.history_next(&self.history);
+        }
+    }
+
+    /// Completes the word before the cursor of the command line
+    pub fn complete_command_line(&mut self) {
+        if let Some(mut command_line) = self.command_line.take() {
+            command_line.complete(|input| commands::complete(self, input));
+            self.command_line = Some(command_line);
+        }
+    }
+
+    /// Executes the contents of the command line and closes it
+    pub fn submit_command_line(&mut self) {
+        if let Some(command_line) = self.command_line.take() {
+            if let Err(why) = self.history.add(command_line.input()) {
+                self.show_error(why.context("failed to save the history"));
+            }
+            if let Err(why) = commands::execute(self, command_line.input()) {
+                self.show_error(why);
+            }
+        }
+    }
+
+    /// Displays only rows which match the filter, in addition to the
+    /// existing filters
+    pub fn add_filter(&mut self, filter: RowFilter) {
+        self.tab_mut().add_filter(filter);
+        self.sync_secondary_pane();
+    }
+
+    pub fn clear_filters(&mut self) {
+        self.tab_mut().clear_filters();
+        self.sync_secondary_pane();
+    }
+
+    pub fn set_time_range(&mut self, range: Option<TimeRange>) {
+        self.tab_mut().set_time_range(range);
+        self.sync_secondary_pane();
+    }
+
+    /// Moves the cursor to a visible row, where `0` is the first row
+    pub fn goto(&mut self, row: usize) {
+        let len = self.tab().visible_rows().len();
+        if len > 0 {
+            self.set_cursor(row.min(len - 1));
+        }
+    }
+
+    fn set_cursor(&mut self, cursor: usize) {
+        self.tab_mut().viewstate_mut().set_cursor(cursor);
+        self.sync_secondary_pane();
+    }
+
     pub fn forward(&mut self, steps: usize) {
-        if !self.data().is_empty() {
-            self.viewstate.set_vscroll_offset(usize::min(
-                *self.viewstate().vscroll_offset() + steps,
-                self.data().len() - 1,
-            ));
+        let len = self.tab().visible_rows().len();
+        if len > 0 {
+            let cursor = *self.tab().viewstate().cursor();
+            self.set_cursor(usize::min(cursor + steps, len - 1));
         }
     }
 
     pub fn backward(&mut self, steps: usize) {
-        self.viewstate
-            .set_vscroll_offset(usize::max(*self.viewstate.vscroll_offset(), steps) - steps);
+        let cursor = *self.tab().viewstate().cursor();
+        self.set_cursor(usize::max(cursor, steps) - steps);
+    }
+
+    /// Moves the cursor forward by `steps` pages of `lines` terminal lines
+    pub fn page_forward(&mut self, lines: usize, steps: usize) {
+        for _ in 0..steps {
+            self.forward(self.rows_in_lines(lines, true));
+        }
+    }
+
+    /// Moves the cursor backward by `steps` pages of `lines` terminal lines
+    pub fn page_backward(&mut self, lines: usize, steps: usize) {
+        for _ in 0..steps {
+            self.backward(self.rows_in_lines(lines, false));
+        }
+    }
+
+    /// Number of rows next to the cursor which fit into `lines` terminal
+    /// lines, but at least `1`. Moving forward, this includes the row at the
+    /// cursor.
+    fn rows_in_lines(&self, lines: usize, forward: bool) -> usize {
+        if !self.wrap {
+            return lines.max(1);
+        }
+        let tab = self.tab();
+        let cursor = *tab.viewstate().cursor();
+        let positions: Box<dyn Iterator<Item = usize>> = if forward {
+            Box::new(cursor..tab.visible_rows().len())
+        } else {
+            Box::new((0..cursor).rev())
+        };
+        let layout = WrapLayout::new(tab.data().as_ref(), *tab.viewstate().data_width());
+        let mut used = 0;
+        let mut rows = 0;
+        for position in positions {
+            let record = tab.visible_rows()[position];
+            let raw = Self::raw_line(self.raw_view, tab, record);
+            used += layout.height(
+                tab.data().as_ref(),
+                record,
+                raw.as_deref(),
+                self.mask_unicode,
+            );
+            if used > lines {
+                break;
+            }
+            rows += 1;
+        }
+        rows.max(1)
     }
 
     pub fn begin(&mut self) {
-        self.viewstate.set_vscroll_offset(0);
+        self.set_cursor(0);
     }
 
     pub fn end(&mut self) {
-        if !self.data().is_empty() {
-            self.viewstate.set_vscroll_offset(self.data().len() - 1);
+        let len = self.tab().visible_rows().len();
+        if len > 0 {
+            self.set_cursor(len - 1);
         }
     }
 
     pub fn right(&mut self, steps: usize) {
-        let viewport = ViewPort::new(self.viewstate.hscroll_offset() + steps, 0, 44, 55);
-        let width: usize = self.data.data_widths(&viewport).sum();
+        let hscroll_offset = *self.tab().viewstate().hscroll_offset();
+        let viewport = ViewPort::new(hscroll_offset + steps, 0, 44, 55);
+        let width: usize = self.tab().data().data_widths(&viewport).sum();
         if width > 0 {
-            self.viewstate
-                .set_hscroll_offset(self.viewstate.hscroll_offset() + steps);
+            self.set_hscroll_offset(hscroll_offset + steps);
         }
     }
 
     pub fn left(&mut self, steps: usize) {
-        if *self.viewstate.hscroll_offset() >= steps {
-            self.viewstate
-                .set_hscroll_offset(self.viewstate.hscroll_offset() - steps);
+        let hscroll_offset = *self.tab().viewstate().hscroll_offset();
+        self.set_hscroll_offset(hscroll_offset.saturating_sub(steps));
+    }
+
+    /// Scrolls horizontally and focuses the column at the left edge
+    fn set_hscroll_offset(&mut self, hscroll_offset: usize) {
+        let column = self.tab().data().column_at(hscroll_offset);
+        let viewstate = self.tab_mut().viewstate_mut();
+        viewstate.set_hscroll_offset(hscroll_offset);
+        viewstate.set_column(column);
+    }
+
+    /// Focuses a column and scrolls to its beginning
+    pub fn focus_column(&mut self, column: usize) {
+        let tab = self.tab_mut();
+        let column = usize::min(column, tab.data().column_count().saturating_sub(1));
+        let offset = tab.data().column_offset(column);
+        let viewstate = tab.viewstate_mut();
+        viewstate.set_hscroll_offset(offset);
+        viewstate.set_column(column);
+    }
+
+    pub fn next_column(&mut self) {
+        self.focus_column(self.tab().viewstate().column() + 1);
+    }
+
+    pub fn previous_column(&mut self) {
+        self.focus_column(self.tab().viewstate().column().saturating_sub(1));
+    }
+
+    /// Column and value of the focused cell
+    pub fn focused_cell(&self) -> Option<(usize, String)> {
+        let tab = self.tab();
+        let column = *tab.viewstate().column();
+        let line = tab.data().line(tab.cursor_record()?)?;
+        Some((column, line.value(column)?.to_string()))
+    }
+
+    /// Displays only rows which have the same value in the focused column
+    pub fn pivot_on_column(&mut self) {
+        if let Some((column, value)) = self.focused_cell() {
+            let column_name = self
+                .tab()
+                .data()
+                .column_info(column)
+                .map(|info| info.name().clone())
+                .unwrap_or_default();
+            self.tab_mut().pivot(RowFilter::ColumnEquals {
+                column,
+                column_name,
+                value,
+            });
+            self.sync_secondary_pane();
+        }
+    }
+
+    /// Displays only rows which contain the value of the focused cell in any
+    /// column
+    pub fn pivot_on_value(&mut self) {
+        if let Some((_, value)) = self.focused_cell() {
+            self.tab_mut().pivot(RowFilter::Contains(value));
+            self.sync_secondary_pane();
+        }
+    }
+
+    /// Returns to the view before the last pivot
+    pub fn back(&mut self) {
+        if self.tab_mut().back() {
+            self.sync_secondary_pane();
         } else {
-            self.viewstate.set_hscroll_offset(0);
+            self.message = Some("there is no previous view".into());
         }
     }
 
-    pub fn render_log_contents(&mut self, frame: &mut Frame, area: Rect) {
-        let mut viewstate = *self.csv_viewstate();
-        frame.render_stateful_widget(
-            LogView::from(self.data.as_ref()).with_mask_unicode(*self.cli.mask_unicode()),
-            area,
-            &mut viewstate,
-        );
-        self.viewstate = viewstate;
+    /// Copies the current row to the clipboard
+    pub fn yank_row(&mut self) {
+        let rows = self.tab().cursor_record().into_iter().collect::<Vec<_>>();
+        self.yank_rows(&rows);
+    }
+
+    /// Copies the selected rows to the clipboard and clears the selection
+    pub fn yank_selection(&mut self) {
+        let rows = self.tab().selected_rows().to_vec();
+        self.yank_rows(&rows);
+        self.tab_mut().clear_selection();
+    }
+
+    fn yank_rows(&mut self, rows: &[usize]) {
+        match self.yank_format.serialize(self.tab().data().as_ref(), rows) {
+            Ok(text) if rows.len() == 1 => self.yank(&text, "1 row"),
+            Ok(text) => self.yank(&text, &format!("{} rows", rows.len())),
+            Err(why) => self.show_error(why.context("unable to copy the rows")),
+        }
+    }
+
+    /// Copies the value of the focused cell to the clipboard
+    pub fn yank_cell(&mut self) {
+        if let Some((_, value)) = self.focused_cell() {
+            self.yank(&value, "cell");
+        }
+    }
+
+    fn yank(&mut self, text: &str, what: &str) {
+        let size_warning = if exceeds_clipboard_limit(text) {
+            format!(" ({} bytes are too large for the terminal)", text.len())
+        } else {
+            String::new()
+        };
+        match copy_to_clipboard(text) {
+            Ok(ClipboardTarget::Terminal) => {
+                self.message = Some(format!("copied {what} to the clipboard"));
+            }
+            Ok(ClipboardTarget::File(path)) => {
+                self.message = Some(format!("copied {what} to {}{size_warning}", path.display()));
+            }
+            Err(why) => self.show_error(why.context(format!("unable to copy {what}"))),
+        }
+    }
+
+    /// Adds a bookmark to the current row, or removes it
+    pub fn toggle_bookmark(&mut self) {
+        if let Some(record) = self.tab().cursor_record() {
+            self.tab_mut().bookmarks_mut().toggle(record);
+        }
+    }
+
+    /// Assigns the named mark to the current row
+    pub fn set_mark(&mut self, name: char) {
+        if let Some(record) = self.tab().cursor_record() {
+            self.tab_mut().bookmarks_mut().set_mark(name, record);
+            self.message = Some(format!("mark '{name}' set"));
+        }
+    }
+
+    pub fn jump_to_mark(&mut self, name: char) {
+        match self.tab().bookmarks().mark(name) {
+            Some(record) => self.jump_to_record(record),
+            None => self.message = Some(format!("mark '{name}' is not set")),
+        }
+    }
+
+    pub fn next_bookmark(&mut self) {
+        match self.tab().next_bookmark() {
+            Some(position) => self.set_cursor(position),
+            None => self.message = Some("there is no next bookmark".into()),
+        }
+    }
+
+    pub fn previous_bookmark(&mut self) {
+        match self.tab().previous_bookmark() {
+            Some(position) => self.set_cursor(position),
+            None => self.message = Some("there is no previous bookmark".into()),
+        }
+    }
+
+    /// Displays all bookmarks and marks of the current tab in an overlay
+    pub fn show_bookmarks(&mut self) {
+        let tab = self.tab();
+        let bookmarks = tab.bookmarks();
+        let items = bookmarks
+            .records()
+            .into_iter()
+            .filter_map(|record| {
+                let line = tab.data().line(record)?;
+                let name = match bookmarks.mark_of(record) {
+                    Some(name) => format!("'{name}"),
+                    None => "•".into(),
+                };
+                let contents = line.iter_contents().collect::<Vec<_>>().join(" ");
+                Some(OverlayItem::new(
+                    format!("{name:>2} {}: {contents}", line.key_value()),
+                    OverlayAction::JumpToRecord(record),
+                ))
+            })
+            .collect();
+        self.overlay = Some(Overlay::new("bookmarks".into(), items));
+    }
+
+    /// Modifies the annotation of the current row
+    pub(crate) fn annotate(
+        &mut self,
+        f: impl FnOnce(&mut Annotations, usize),
+    ) -> anyhow::Result<()> {
+        match self.tab().cursor_record() {
+            Some(record) => self.tab_mut().annotate(record, f),
+            None => anyhow::bail!("there is no row to annotate"),
+        }
+    }
+
+    /// Displays the key bindings of all actions in an overlay, grouped by
+    /// category
+    pub fn show_help(&mut self) {
+        let mut actions = Action::value_variants().to_vec();
+        actions.sort_by_key(Action::category);
+        let mut items = Vec::new();
+        for (idx, action) in actions.iter().enumerate() {
+            if idx == 0 || actions[idx - 1].category() != action.category() {
+                items.push(OverlayItem::heading(action.category().to_string()));
+            }
+            let keys = self.keymap.keys_of(*action).join(" ");
+            items.push(OverlayItem::new(
+                format!("{keys:<20} {:<18} {}", action.name(), action.description()),
+                OverlayAction::None,
+            ));
+        }
+        self.overlay = Some(Overlay::new("help".into(), items).with_filter());
+    }
+
+    /// Tells the user if the current tab could not be parsed properly
+    fn report_load_problems(&mut self) {
+        let count = self.tab().data().parse_problems().len();
+        if count > 0 {
+            self.message = Some(format!(
+                "{count} rows could not be parsed properly, see :problems"
+            ));
+        }
+        if let Some(why) = self.tab().annotations().load_error() {
+            let warning = format!("ignoring the annotations: {why}");
+            self.message = Some(match self.message.take() {
+                Some(message) => format!("{message}; {warning}"),
+                None => warning,
+            });
+        }
+    }
+
+    /// Displays the parts of the input of the current tab which could not be
+    /// parsed properly in an overlay
+    pub fn show_parse_problems(&mut self) {
+        let items = self
+            .tab()
+            .data()
+            .parse_problems()
+            .iter()
+            .map(|problem| {
+                OverlayItem::new(
+                    problem.to_string(),
+                    OverlayAction::JumpToRecord(*problem.record()),
+                )
+            })
+            .collect();
+        self.overlay = Some(Overlay::new("parse problems".into(), items));
+    }
+
+    /// Displays all annotated rows of the current tab in an overlay
+    pub fn show_annotations(&mut self) {
+        let tab = self.tab();
+        let items = tab
+            .annotations()
+            .iter()
+            .filter_map(|(record, annotation)| {
+                let line = tab.data().line(*record)?;
+                let tags = annotation
+                    .tags()
+                    .iter()
+                    .map(|tag| format!("#{tag} "))
+                    .collect::<String>();
+                Some(OverlayItem::new(
+                    format!("{}: {tags}{}", line.key_value(), annotation.note()),
+                    OverlayAction::JumpToRecord(*record),
+                ))
+            })
+            .collect();
+        let header = vec![format!(
+            "tags: {}",
+            tab.annotations()
+                .tags()
+                .into_iter()
+                .collect::<Vec<_>>()
+                .join(", ")
+        )];
+        self.overlay = Some(Overlay::new("annotations".into(), items).with_header(header));
+    }
+
+    /// Returns the unparsed line of a record if it is displayed in the raw
+    /// view
+    fn raw_line(raw_view: RawView, tab: &Tab, record: usize) -> Option<String> {
+        raw_view
+            .shows(record, tab.cursor_record())
+            .then(|| match tab.data().raw_line(record) {
+                Ok(raw) => escape_line_breaks(&raw),
+                Err(why) => format!("unable to read the raw line: {why:#}"),
+            })
+    }
+
+    pub fn render_log_contents(&mut self, frame: &mut Frame, area: Rect, tab_idx: usize) {
+        let mask_unicode = self.mask_unicode;
+        let tab = &mut self.tabs[tab_idx];
+        let mut viewstate = *tab.viewstate();
+        let gutter = |record| tab.gutter_symbol(record);
+        let raw_view = self.raw_view;
+        let raw_line = |record| Self::raw_line(raw_view, tab, record);
+        let mut log_view = LogView::new(tab.data().as_ref(), tab.visible_rows())
+            .with_mask_unicode(mask_unicode)
+            .with_timestamp_display(self.timestamp_display)
+            .with_selection(tab.selection())
+            .with_raw_lines(&raw_line)
+            .with_wrap(self.wrap);
+        if tab.has_gutter() {
+            log_view = log_view.with_gutter(&gutter);
+        }
+        frame.render_stateful_widget(log_view, area, &mut viewstate);
+        tab.set_viewstate(viewstate);
+    }
+
+    pub fn render_overlay(&mut self, frame: &mut Frame, area: Rect) {
+        if let Some(overlay) = self.overlay.as_mut() {
+            frame.render_widget(overlay, area);
+        }
+    }
+
+    pub fn render_timeline(&mut self, frame: &mut Frame, area: Rect) {
+        let tab = &mut self.tabs[self.active_tab];
+        if tab.timeline(area.width.into()).is_some() {
+            let current_bucket = tab.current_bucket();
+            if let Some(timeline) = tab.cached_timeline() {
+                frame.render_widget(TimelineView::new(timeline, current_bucket), area);
+            }
+            self.timeline_area = Some(area);
+        } else {
+            frame.render_widget(
+                Paragraph::new("the index column contains no timestamps").dark_gray(),
+                area,
+            );
+            self.timeline_area = None;
+        }
     }
 
     pub fn csv_viewstate(&self) -> &LogViewState {
-        &self.viewstate
+        self.tab().viewstate()
     }
 }
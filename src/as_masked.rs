@@ -1,39 +1,47 @@
-use std::ops::RangeBounds;
+use std::{collections::VecDeque, fmt::Display, iter::Peekable, ops::RangeBounds};
 
 use ratatui::{
-    style::{Color, Stylize},
+    style::{Color, Style, Stylize},
     text::{Line, Span},
 };
 use unicode_segmentation::{GraphemeIndices, UnicodeSegmentation};
 use unicode_width::UnicodeWidthStr;
 
+use crate::Homoglyph;
+
 const INTERESTING_STRINGS: [&str; 2] = ["krbtgt", "admin"];
 
 pub trait AsMasked {
-    fn as_masked<R: RangeBounds<usize> + std::slice::SliceIndex<str>>(
-        &self,
+    /// Renders a part of the text. `homoglyphs` are the words of the text
+    /// which are highlighted because they mix scripts or look like other
+    /// words, see [`crate::find_homoglyphs`].
+    fn as_masked<'a, R: RangeBounds<usize> + std::slice::SliceIndex<str>>(
+        &'a self,
         range: R,
         mask_unicode: bool,
-    ) -> Line<'_>;
+        homoglyphs: &'a [Homoglyph],
+    ) -> Line<'a>;
 }
 
 impl AsMasked for String {
-    fn as_masked<R: RangeBounds<usize> + std::slice::SliceIndex<str>>(
-        &self,
+    fn as_masked<'a, R: RangeBounds<usize> + std::slice::SliceIndex<str>>(
+        &'a self,
         range: R,
         mask_unicode: bool,
-    ) -> Line<'_> {
-        do_as_masked(self, range, &INTERESTING_STRINGS, mask_unicode)
+        homoglyphs: &'a [Homoglyph],
+    ) -> Line<'a> {
+        do_as_masked(self, range, &INTERESTING_STRINGS, mask_unicode, homoglyphs)
     }
 }
 
 impl AsMasked for str {
-    fn as_masked<R: RangeBounds<usize> + std::slice::SliceIndex<str>>(
-        &self,
+    fn as_masked<'a, R: RangeBounds<usize> + std::slice::SliceIndex<str>>(
+        &'a self,
         range: R,
         mask_unicode: bool,
-    ) -> Line<'_> {
-        do_as_masked(self, range, &INTERESTING_STRINGS, mask_unicode)
+        homoglyphs: &'a [Homoglyph],
+    ) -> Line<'a> {
+        do_as_masked(self, range, &INTERESTING_STRINGS, mask_unicode, homoglyphs)
     }
 }
 
@@ -42,6 +50,7 @@ fn do_as_masked<'a, R: RangeBounds<usize> + std::slice::SliceIndex<str>>(
     range: R,
     interesting_strings: &[&str],
     mask_unicode: bool,
+    homoglyphs: &'a [Homoglyph],
 ) -> Line<'a> {
     let l = s.to_lowercase();
 
@@ -53,15 +62,10 @@ fn do_as_masked<'a, R: RangeBounds<usize> + std::slice::SliceIndex<str>>(
         }
     }
 
-    let line = if mask_unicode {
-        Line::from(s.iter_spans(range).collect::<Vec<Span>>())
-    } else {
-        Line::from(
-            s.grapheme_indices(true)
-                .filter_map(|(idx, s)| if range.contains(&idx) { Some(s) } else { None })
-                .collect::<String>(),
-        )
-    };
+    let line = Line::from(
+        s.iter_spans(range, mask_unicode, homoglyphs)
+            .collect::<Vec<Span>>(),
+    );
 
     if is_interesting {
         line.red().on_black()
@@ -70,19 +74,155 @@ fn do_as_masked<'a, R: RangeBounds<usize> + std::slice::SliceIndex<str>>(
     }
 }
 
+/// Kinds of characters which are used to hide or disguise text, e.g. to
+/// spoof file names
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SuspiciousChar {
+    /// changes the direction of the text, e.g. `U+202E`
+    Bidi,
+
+    /// invisible characters like `U+200B` or `U+200D`
+    ZeroWidth,
+
+    /// a byte order mark which is not at the start of the input
+    ByteOrderMark,
+
+    /// invisible tag characters from `U+E0000` to `U+E007F`
+    Tag,
+
+    /// characters without a meaning defined by Unicode
+    PrivateUse,
+
+    /// control characters other than tab and line breaks
+    Control,
+}
+
+impl SuspiciousChar {
+    pub fn classify(ch: char) -> Option<Self> {
+        match ch {
+            '\u{200e}'
+            | '\u{200f}'
+            | '\u{061c}'
+            | '\u{202a}'..='\u{202e}'
+            | '\u{2066}'..='\u{2069}' => Some(Self::Bidi),
+            '\u{200b}'..='\u{200d}' | '\u{2060}'..='\u{2064}' | '\u{180e}' => Some(Self::ZeroWidth),
+            '\u{feff}' => Some(Self::ByteOrderMark),
+            '\u{e0000}'..='\u{e007f}' => Some(Self::Tag),
+            '\u{e000}'..='\u{f8ff}' | '\u{f0000}'..='\u{ffffd}' | '\u{100000}'..='\u{10fffd}' => {
+                Some(Self::PrivateUse)
+            }
+            '\t' | '\n' | '\r' => None,
+            ch if ch.is_control() => Some(Self::Control),
+            _ => None,
+        }
+    }
+
+    /// style of masked characters of this kind
+    pub fn style(&self) -> Style {
+        match self {
+            Self::Bidi => Style::new().fg(Color::White).bg(Color::Magenta).bold(),
+            Self::ZeroWidth => Style::new().fg(Color::Black).bg(Color::Yellow),
+            Self::ByteOrderMark => Style::new().fg(Color::Black).bg(Color::Cyan),
+            Self::Tag => Style::new().fg(Color::White).bg(Color::Blue),
+            Self::PrivateUse => Style::new().fg(Color::Black).bg(Color::LightGreen),
+            Self::Control => Style::new().fg(Color::White).bg(Color::Red).bold(),
+        }
+    }
+}
+
+impl Display for SuspiciousChar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Bidi => "bidi control",
+            Self::ZeroWidth => "zero-width character",
+            Self::ByteOrderMark => "byte order mark",
+            Self::Tag => "tag character",
+            Self::PrivateUse => "private-use character",
+            Self::Control => "control character",
+        })
+    }
+}
+
+/// Returns the suspicious characters of a text
+pub fn suspicious_chars(s: &str) -> impl Iterator<Item = (SuspiciousChar, char)> + '_ {
+    s.chars()
+        .filter_map(|ch| SuspiciousChar::classify(ch).map(|kind| (kind, ch)))
+}
+
+/// Returns the escaped spans of a grapheme cluster which needs to be masked,
+/// or nothing if the grapheme cluster can be displayed as it is
+fn mask_grapheme(grapheme_cluster: &str) -> Vec<Span<'static>> {
+    let other = Style::new().fg(Color::LightYellow).bg(Color::Red);
+    let escaped = |ch: char| ch.escape_unicode().to_string();
+
+    if grapheme_cluster
+        .chars()
+        .any(|ch| SuspiciousChar::classify(ch).is_some())
+    {
+        grapheme_cluster
+            .chars()
+            .map(|ch| match SuspiciousChar::classify(ch) {
+                Some(kind) => Span::styled(escaped(ch), kind.style()),
+                None if ch.is_alphanumeric() || ch.is_whitespace() || ch.is_ascii() => {
+                    Span::raw(ch.to_string())
+                }
+                None => Span::styled(escaped(ch), other),
+            })
+            .collect()
+    } else if grapheme_cluster.len() == 1 {
+        let ch = grapheme_cluster.chars().next().unwrap();
+        if ch.is_alphanumeric() || ch.is_whitespace() || ch.is_ascii() {
+            Vec::new()
+        } else {
+            vec![Span::styled(escaped(ch), other)]
+        }
+    } else if grapheme_cluster.width() < 1 {
+        vec![Span::styled(
+            grapheme_cluster.chars().map(escaped).collect::<String>(),
+            other,
+        )]
+    } else {
+        // might be something like 'y̆', which is a combination of 'y' and '\u{0306}'
+        Vec::new()
+    }
+}
+
+/// style of words which mix scripts or look like ASCII words; the
+/// characters which are not ASCII are reversed in addition
+fn homoglyph_style(grapheme_cluster: &str) -> Style {
+    let style = Style::new().fg(Color::LightRed).underlined();
+    if grapheme_cluster.is_ascii() {
+        style
+    } else {
+        style.reversed()
+    }
+}
+
 trait IterSpans<R: RangeBounds<usize> + std::slice::SliceIndex<str>> {
-    fn iter_spans(&self, range: R) -> MaskedSpans<'_, R>;
+    fn iter_spans<'a>(
+        &'a self,
+        range: R,
+        mask_unicode: bool,
+        homoglyphs: &'a [Homoglyph],
+    ) -> MaskedSpans<'a, R>;
 }
 
 impl<R> IterSpans<R> for str
 where
     R: RangeBounds<usize> + std::slice::SliceIndex<str>,
 {
-    fn iter_spans(&self, range: R) -> MaskedSpans<'_, R> {
+    fn iter_spans<'a>(
+        &'a self,
+        range: R,
+        mask_unicode: bool,
+        homoglyphs: &'a [Homoglyph],
+    ) -> MaskedSpans<'a, R> {
         MaskedSpans {
-            graphemes: self.grapheme_indices(true),
+            graphemes: self.grapheme_indices(true).peekable(),
             range,
-            defered_span: None,
+            mask_unicode,
+            defered_spans: VecDeque::new(),
+            homoglyphs,
         }
     }
 }
@@ -91,10 +231,32 @@ struct MaskedSpans<'a, R>
 where
     R: RangeBounds<usize> + std::slice::SliceIndex<str>,
 {
-    graphemes: GraphemeIndices<'a>,
+    graphemes: Peekable<GraphemeIndices<'a>>,
     range: R,
-    defered_span: Option<Span<'a>>,
+
+    /// display special characters as code points
+    mask_unicode: bool,
+    defered_spans: VecDeque<Span<'a>>,
+    homoglyphs: &'a [Homoglyph],
+}
+
+impl<'a, R> MaskedSpans<'a, R>
+where
+    R: RangeBounds<usize> + std::slice::SliceIndex<str>,
+{
+    fn style_at(&self, idx: usize, grapheme_cluster: &str) -> Style {
+        if self
+            .homoglyphs
+            .iter()
+            .any(|homoglyph| homoglyph.range().contains(&idx))
+        {
+            homoglyph_style(grapheme_cluster)
+        } else {
+            Style::new()
+        }
+    }
 }
+
 impl<'a, R> Iterator for MaskedSpans<'a, R>
 where
     R: RangeBounds<usize> + std::slice::SliceIndex<str>,
@@ -102,60 +264,103 @@ where
     type Item = Span<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut current_span_content = Vec::new();
+        let mut current_span_content = String::new();
+        let mut current_style = None;
 
-        if let Some(defered_span) = self.defered_span.take() {
+        if let Some(defered_span) = self.defered_spans.pop_front() {
             return Some(defered_span);
         }
 
-        for (idx, grapheme_cluster) in self.graphemes.by_ref() {
-            if self.range.contains(&idx) {
-                if grapheme_cluster.len() == 1 {
-                    let ch = grapheme_cluster.chars().next().unwrap();
-
-                    if ch.is_alphanumeric() || ch.is_whitespace() || ch.is_ascii() {
-                        current_span_content.push(ch)
-                    } else {
-                        let highlighted_span = Span::raw(ch.escape_unicode().collect::<String>())
-                            .fg(Color::LightYellow)
-                            .bg(Color::Red);
-
-                        if current_span_content.is_empty() {
-                            return Some(highlighted_span);
-                        } else {
-                            self.defered_span = Some(highlighted_span);
-                            break;
-                        }
-                    }
-                } else if grapheme_cluster.width() < 1 {
-                    let highlighted_span = Span::raw(
-                        grapheme_cluster
-                            .chars()
-                            .map(|ch| ch.escape_unicode().to_string())
-                            .collect::<String>(),
-                    )
-                    .fg(Color::LightYellow)
-                    .bg(Color::Red);
-
-                    if current_span_content.is_empty() {
-                        return Some(highlighted_span);
-                    } else {
-                        self.defered_span = Some(highlighted_span);
-                        break;
-                    }
-                } else {
-                    // might be something like 'y̆', which is a combination of 'y' and '\u{0306}'
-                    current_span_content.extend(grapheme_cluster.chars())
+        while let Some((idx, grapheme_cluster)) = self.graphemes.peek().copied() {
+            if !self.range.contains(&idx) {
+                self.graphemes.next();
+                continue;
+            }
+            let masked = if self.mask_unicode {
+                mask_grapheme(grapheme_cluster)
+            } else {
+                Vec::new()
+            };
+            if !masked.is_empty() {
+                if !current_span_content.is_empty() {
+                    break;
                 }
+                self.graphemes.next();
+                self.defered_spans.extend(masked);
+                return self.defered_spans.pop_front();
+            }
+
+            // a change of the style ends the span
+            let style = self.style_at(idx, grapheme_cluster);
+            if current_style.is_some_and(|current| current != style) {
+                break;
             }
+            current_style = Some(style);
+            current_span_content.push_str(grapheme_cluster);
+            self.graphemes.next();
         }
 
         if current_span_content.is_empty() {
             None
         } else {
-            Some(Span::raw(
-                current_span_content.into_iter().collect::<String>(),
+            Some(Span::styled(
+                current_span_content,
+                current_style.unwrap_or_default(),
             ))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{suspicious_chars, AsMasked, SuspiciousChar};
+
+    #[test]
+    fn test_classify() {
+        let cases = [
+            ('\u{202e}', SuspiciousChar::Bidi),
+            ('\u{2066}', SuspiciousChar::Bidi),
+            ('\u{200b}', SuspiciousChar::ZeroWidth),
+            ('\u{feff}', SuspiciousChar::ByteOrderMark),
+            ('\u{e0041}', SuspiciousChar::Tag),
+            ('\u{e000}', SuspiciousChar::PrivateUse),
+            ('\u{10fffd}', SuspiciousChar::PrivateUse),
+            ('\u{1b}', SuspiciousChar::Control),
+            ('\u{7f}', SuspiciousChar::Control),
+        ];
+        for (ch, expected) in cases {
+            assert_eq!(SuspiciousChar::classify(ch), Some(expected), "{ch:?}");
+        }
+
+        for ch in ['\t', '\n', '\r', ' ', 'a', 'ä', '日', '😀'] {
+            assert_eq!(SuspiciousChar::classify(ch), None, "{ch:?}");
+        }
+    }
+
+    #[test]
+    fn test_suspicious_chars() {
+        let found: Vec<_> = suspicious_chars("a\u{feff}b\tc\u{202e}d\r\n").collect();
+        assert_eq!(
+            found,
+            [
+                (SuspiciousChar::ByteOrderMark, '\u{feff}'),
+                (SuspiciousChar::Bidi, '\u{202e}'),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_masking() {
+        let line = "exe.\u{202e}txt".as_masked(.., true, &[]);
+        assert_eq!(line.to_string(), "exe.\\u{202e}txt");
+        let masked = line
+            .spans
+            .iter()
+            .find(|span| span.content == "\\u{202e}")
+            .unwrap();
+        assert_eq!(masked.style, SuspiciousChar::Bidi.style());
+
+        // tabs are displayed as they are
+        assert_eq!("a\tb".as_masked(.., true, &[]).to_string(), "a\tb");
+    }
+}
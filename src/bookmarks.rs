@@ -0,0 +1,59 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use getset::Getters;
+use serde::{Deserialize, Serialize};
+
+/// Bookmarked records and vim-style named marks. Both refer to record
+/// indices, so they stay valid regardless of the rows which are visible.
+#[derive(Clone, Debug, Default, Getters, Serialize, Deserialize)]
+#[getset(get = "pub")]
+pub struct Bookmarks {
+    bookmarks: BTreeSet<usize>,
+    marks: BTreeMap<char, usize>,
+}
+
+impl Bookmarks {
+    /// Adds a bookmark to the record, or removes it if there is one.
+    /// Returns `true` if the record is bookmarked now.
+    pub fn toggle(&mut self, record: usize) -> bool {
+        if self.bookmarks.remove(&record) {
+            false
+        } else {
+            self.bookmarks.insert(record);
+            true
+        }
+    }
+
+    pub fn set_mark(&mut self, name: char, record: usize) {
+        self.marks.insert(name, record);
+    }
+
+    pub fn mark(&self, name: char) -> Option<usize> {
+        self.marks.get(&name).copied()
+    }
+
+    pub fn is_bookmarked(&self, record: usize) -> bool {
+        self.bookmarks.contains(&record)
+    }
+
+    /// Name of the first mark which refers to the record
+    pub fn mark_of(&self, record: usize) -> Option<char> {
+        self.marks
+            .iter()
+            .find(|(_, r)| **r == record)
+            .map(|(name, _)| *name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bookmarks.is_empty() && self.marks.is_empty()
+    }
+
+    /// All bookmarked or marked records, in ascending order
+    pub fn records(&self) -> BTreeSet<usize> {
+        self.bookmarks
+            .iter()
+            .chain(self.marks.values())
+            .copied()
+            .collect()
+    }
+}
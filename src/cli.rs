@@ -1,7 +1,13 @@
+use std::{io::IsTerminal, path::PathBuf};
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use clap::{Parser, ValueEnum};
 use clio::Input;
 use getset::Getters;
 
+use crate::{ExportFormat, KeymapPreset};
+
 #[derive(Clone, Debug, ValueEnum)]
 pub enum FileFormat {
     Csv,
@@ -25,13 +31,107 @@ pub struct Cli {
     delimiter: char,
 
     /// Mask unicode characters.
-    /// 
+    ///
     /// Normally, all characters are displayed as they are. But there are some
     /// special characters which would not be visible this way, such as the
     /// Left-to-Right-Mark. To display such characters, you need to enable this
     /// switch. The result will be that any characters which are not any of
     /// alphanumeric, whitespace or ascii will be displayed as Unicode code
-    /// point (e.g. U-200E) and highlighted using a color (preferably red)
+    /// point (e.g. U-200E) and highlighted using a color (preferably red).
+    /// Bidi controls, zero-width, tag, private-use and control characters
+    /// get colors of their own. Press 'U' to toggle masking at runtime.
+    /// Words which mix scripts or look like ASCII words are underlined in
+    /// both cases.
     #[clap(long("mask-unicode"), short('U'))]
-    mask_unicode: bool
+    mask_unicode: bool,
+
+    /// display long rows over multiple lines instead of scrolling
+    /// horizontally
+    #[clap(long("wrap"))]
+    wrap: bool,
+
+    /// Timezone to display timestamps in, e.g. 'Europe/Berlin'.
+    ///
+    /// Timestamps in the index column are parsed and can be displayed as
+    /// found in the file, in UTC or in this timezone (toggle with 't').
+    #[clap(long("tz"))]
+    timezone: Option<Tz>,
+
+    /// only display rows with a timestamp at or after this time
+    #[clap(long("from"), value_parser = parse_time_bound)]
+    from: Option<DateTime<Utc>>,
+
+    /// only display rows with a timestamp at or before this time
+    #[clap(long("to"), value_parser = parse_time_bound)]
+    to: Option<DateTime<Utc>>,
+
+    /// keep rows whose index value cannot be parsed as timestamp when
+    /// restricting the time range
+    #[clap(long("keep-unparsed"))]
+    keep_unparsed: bool,
+
+    /// key bindings to use, instead of the preset in the configuration file
+    #[clap(long("keymap"), value_enum)]
+    keymap: Option<KeymapPreset>,
+
+    /// do not restore the last view of the file, and do not save it on exit
+    #[clap(long("no-session"))]
+    no_session: bool,
+
+    /// only display rows where the column has this value, e.g. 'user=alice'
+    #[clap(long("filter"), value_name = "COLUMN=VALUE")]
+    filters: Vec<String>,
+
+    /// only display rows where any column contains this text
+    #[clap(long("grep"), value_name = "TEXT")]
+    grep: Vec<String>,
+
+    /// only display rows which are annotated with this tag
+    #[clap(long("tag"))]
+    tags: Vec<String>,
+
+    /// write the rows which match the filters to stdout instead of
+    /// displaying them. This is the default if stdout is not a terminal, or
+    /// if there is no terminal to display them on.
+    #[clap(long("batch"), visible_alias("print"))]
+    batch: bool,
+
+    /// write the rows which match the filters to this file (or to stdout if
+    /// it is '-') instead of displaying them
+    #[clap(long("export"))]
+    export: Option<PathBuf>,
+
+    /// format of the printed or exported rows; if not set, it is guessed
+    /// from the extension of the target file, defaulting to aligned text
+    /// on stdout and to CSV otherwise
+    #[clap(long("export-format"), value_enum)]
+    export_format: Option<ExportFormat>,
+
+    /// columns to print or export, by name or number
+    #[clap(long("columns"), value_delimiter = ',')]
+    columns: Vec<String>,
+
+    /// sort the printed or exported rows by this column
+    #[clap(long("sort"), value_name = "COLUMN")]
+    sort: Option<String>,
+
+    /// reverse the order of the printed or exported rows
+    #[clap(long("reverse"))]
+    reverse: bool,
+}
+
+impl Cli {
+    /// Returns `true` if the rows are written to stdout or to a file,
+    /// instead of starting the user interface. This is also the case if
+    /// there is no terminal to display the user interface on.
+    pub fn is_batch(&self) -> bool {
+        self.batch
+            || self.export.is_some()
+            || !std::io::stdout().is_terminal()
+            || !crate::tui::has_terminal()
+    }
+}
+
+fn parse_time_bound(value: &str) -> Result<DateTime<Utc>, String> {
+    crate::parse_timestamp(value).ok_or_else(|| format!("unsupported timestamp format: '{value}'"))
 }
@@ -0,0 +1,168 @@
+use std::{io::Write, path::PathBuf};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::ValueEnum;
+
+use crate::{escape_line_breaks, tui::terminal_output, LogData, LogLine};
+
+/// many terminals silently drop OSC 52 sequences which are larger than this
+const MAX_OSC52_BYTES: usize = 100_000;
+
+/// prefix of the files in the temp directory which receive yanked text if
+/// the terminal cannot
+const CLIPBOARD_FILE_PREFIX: &str = "loghawk-clipboard-";
+
+/// How yanked rows are serialized
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum YankFormat {
+    /// values separated by tabs; backslashes, tabs and line breaks in the
+    /// values are escaped as `\\`, `\t`, `\n` and `\r`
+    #[default]
+    Tsv,
+
+    /// values separated by commas, quoted if necessary
+    Csv,
+
+    /// values separated by spaces, without any quoting; only line breaks
+    /// are escaped, so that every record stays on one line
+    Raw,
+}
+
+impl YankFormat {
+    /// Serializes the records, one line per record
+    pub fn serialize(&self, data: &dyn LogData, rows: &[usize]) -> anyhow::Result<String> {
+        let lines = rows.iter().filter_map(|record| data.line(*record));
+        match self {
+            Self::Csv => {
+                let mut writer = csv::WriterBuilder::new()
+                    .terminator(csv::Terminator::Any(b'\n'))
+                    .from_writer(Vec::new());
+                for line in lines {
+                    writer.write_record(values(line))?;
+                }
+                Ok(String::from_utf8(writer.into_inner()?)?)
+            }
+            Self::Tsv => Ok(lines
+                .map(|line| values(line).map(escape_tsv).collect::<Vec<_>>().join("\t") + "\n")
+                .collect()),
+            Self::Raw => Ok(lines
+                .map(|line| {
+                    values(line)
+                        .map(escape_line_breaks)
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                        + "\n"
+                })
+                .collect()),
+        }
+    }
+}
+
+fn values(line: &LogLine) -> impl Iterator<Item = &str> {
+    std::iter::once(line.key_value()).chain(line.iter_contents())
+}
+
+/// Escapes the characters which would split a value into several values or
+/// records
+fn escape_tsv(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Where yanked text has been copied to
+#[derive(Debug)]
+pub enum ClipboardTarget {
+    /// the terminal has received the text, which it should pass on to the
+    /// system clipboard
+    Terminal,
+
+    /// the text has been written into this file, because it cannot be sent
+    /// to the terminal
+    File(PathBuf),
+}
+
+/// Copies text to the clipboard of the terminal using an OSC 52 escape
+/// sequence. This also works via SSH, but not all terminals support it, so
+/// the text is written into a file if it is too large or if there is no
+/// suitable terminal.
+pub fn copy_to_clipboard(text: &str) -> anyhow::Result<ClipboardTarget> {
+    let encoded = STANDARD.encode(text);
+    if encoded.len() <= MAX_OSC52_BYTES && osc52_supported() {
+        let sequence = format!("\x1b]52;c;{encoded}\x07");
+        let sequence = if std::env::var_os("TMUX").is_some() {
+            // tmux passes the sequence on only if it is wrapped
+            format!("\x1bPtmux;\x1b{sequence}\x1b\\")
+        } else {
+            sequence
+        };
+        let mut terminal = terminal_output()?;
+        terminal.write_all(sequence.as_bytes())?;
+        terminal.flush()?;
+        Ok(ClipboardTarget::Terminal)
+    } else {
+        // the file gets a random name and is only readable by the user, as
+        // the temp directory is shared with other users
+        let (mut file, path) = tempfile::Builder::new()
+            .prefix(CLIPBOARD_FILE_PREFIX)
+            .suffix(".txt")
+            .tempfile()?
+            .keep()?;
+        file.write_all(text.as_bytes())?;
+        Ok(ClipboardTarget::File(path))
+    }
+}
+
+/// Returns `true` if the size of the text exceeds what is sent to the
+/// terminal
+pub fn exceeds_clipboard_limit(text: &str) -> bool {
+    text.len().div_ceil(3) * 4 > MAX_OSC52_BYTES
+}
+
+fn osc52_supported() -> bool {
+    terminal_output().is_ok()
+        && !matches!(
+            std::env::var("TERM").as_deref(),
+            Err(_) | Ok("") | Ok("dumb") | Ok("linux")
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use clio::ClioPath;
+
+    use super::YankFormat;
+    use crate::CsvData;
+
+    #[test]
+    fn test_serialize() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"k,a,b\n1,\"x\ty\",\"two\nlines\"\n2,c:\\d,e f\n")
+            .unwrap();
+        let data = CsvData::try_from(&ClioPath::new(file.path()).unwrap()).unwrap();
+        let serialize = |format: YankFormat| format.serialize(&data, &[0, 1]).unwrap();
+
+        assert_eq!(
+            serialize(YankFormat::Tsv),
+            "1\tx\\ty\ttwo\\nlines\n2\tc:\\\\d\te f\n"
+        );
+        assert_eq!(
+            serialize(YankFormat::Csv),
+            "1,x\ty,\"two\nlines\"\n2,c:\\d,e f\n"
+        );
+        assert_eq!(
+            serialize(YankFormat::Raw),
+            "1 x\ty two\\nlines\n2 c:\\d e f\n"
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    sync::{mpsc, Arc},
+    thread,
+};
+
+use getset::Getters;
+
+use crate::{parse_timestamp, LogData};
+
+/// number of most and least frequent values which are reported
+const TOP_N: usize = 10;
+
+/// Type of the values in a column, which determines how minimum and maximum
+/// are calculated
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueKind {
+    Numeric,
+    Timestamp,
+    Text,
+}
+
+/// Statistics about the values of a single column
+#[derive(Debug, Getters)]
+#[getset(get = "pub")]
+pub struct ColumnStats {
+    /// the column, where `0` is the index column
+    column: usize,
+    column_name: String,
+    rows: usize,
+    distinct: usize,
+    empty: usize,
+    kind: ValueKind,
+    min: Option<String>,
+    max: Option<String>,
+
+    /// most frequent values, together with their number of occurrences
+    top: Vec<(String, usize)>,
+
+    /// least frequent values, together with their number of occurrences
+    bottom: Vec<(String, usize)>,
+}
+
+impl ColumnStats {
+    pub fn compute(data: &dyn LogData, rows: &[usize], column: usize) -> Self {
+        let mut frequencies: HashMap<&str, usize> = HashMap::new();
+        let mut empty = 0;
+        for value in rows
+            .iter()
+            .filter_map(|idx| data.line(*idx))
+            .map(|line| line.value(column).unwrap_or_default())
+        {
+            if value.trim().is_empty() {
+                empty += 1;
+            }
+            *frequencies.entry(value).or_default() += 1;
+        }
+
+        let values = || frequencies.keys().filter(|v| !v.trim().is_empty());
+        let kind = if values().all(|v| v.trim().parse::<f64>().is_ok()) {
+            ValueKind::Numeric
+        } else if values().all(|v| parse_timestamp(v).is_some()) {
+            ValueKind::Timestamp
+        } else {
+            ValueKind::Text
+        };
+
+        let compare = |a: &&&str, b: &&&str| match kind {
+            ValueKind::Numeric => {
+                let a: f64 = a.trim().parse().unwrap_or_default();
+                let b: f64 = b.trim().parse().unwrap_or_default();
+                a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+            }
+            ValueKind::Timestamp => parse_timestamp(a).cmp(&parse_timestamp(b)),
+            ValueKind::Text => a.cmp(b),
+        };
+        let min = values().min_by(compare).map(|v| v.to_string());
+        let max = values().max_by(compare).map(|v| v.to_string());
+
+        let mut sorted: Vec<_> = frequencies.iter().map(|(v, c)| (*v, *c)).collect();
+        sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        let to_owned = |(value, count): &(&str, usize)| (value.to_string(), *count);
+        let top = sorted.iter().take(TOP_N).map(to_owned).collect();
+        let bottom = sorted.iter().rev().take(TOP_N).map(to_owned).collect();
+
+        Self {
+            column,
+            column_name: data
+                .column_info(column)
+                .map(|info| info.name().clone())
+                .unwrap_or_default(),
+            rows: rows.len(),
+            distinct: frequencies.len(),
+            empty,
+            kind,
+            min,
+            max,
+            top,
+            bottom,
+        }
+    }
+
+    /// Computes the statistics on a background thread. The result can be
+    /// received from the returned channel.
+    pub fn spawn(
+        data: Arc<dyn LogData>,
+        rows: Vec<usize>,
+        column: usize,
+    ) -> mpsc::Receiver<ColumnStats> {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(Self::compute(data.as_ref(), &rows, column));
+        });
+        receiver
+    }
+
+    /// share of `count` in all rows, in percent
+    pub fn percentage(&self, count: usize) -> f64 {
+        if self.rows == 0 {
+            0.0
+        } else {
+            count as f64 * 100.0 / self.rows as f64
+        }
+    }
+}
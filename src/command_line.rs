@@ -0,0 +1,380 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+};
+
+use getset::Getters;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    text::Line,
+    widgets::{Paragraph, Widget},
+};
+use unicode_width::UnicodeWidthStr;
+
+/// maximum number of commands which are kept in the history
+const MAX_HISTORY: usize = 1000;
+
+/// number of commands which may exceed [`MAX_HISTORY`] before the history
+/// file is rewritten, so that most commands are simply appended
+const HISTORY_SLACK: usize = 100;
+
+/// Input buffer of the `:` command line
+#[derive(Debug, Default, Getters)]
+#[getset(get = "pub")]
+pub struct CommandLine {
+    input: String,
+
+    /// byte offset of the cursor in `input`
+    cursor: usize,
+
+    /// position in the history, if the input has been taken from it
+    #[getset(skip)]
+    history_position: Option<usize>,
+
+    /// input before the user started to browse the history
+    #[getset(skip)]
+    draft: String,
+
+    #[getset(skip)]
+    completion: Option<Completion>,
+}
+
+/// Candidates for the word which is completed, which are cycled through by
+/// repeatedly requesting a completion
+#[derive(Debug)]
+struct Completion {
+    /// byte offset of the word which is completed
+    start: usize,
+    candidates: Vec<String>,
+    index: usize,
+}
+
+impl CommandLine {
+    /// Creates a command line which already contains some input
+    pub fn with_input(input: String) -> Self {
+        Self {
+            cursor: input.len(),
+            input,
+            ..Default::default()
+        }
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.input.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+        self.completion = None;
+    }
+
+    pub fn push_str(&mut self, s: &str) {
+        // pasted text may contain line breaks, which a command cannot have
+        let s = s.replace(['\r', '\n'], " ");
+        self.input.insert_str(self.cursor, &s);
+        self.cursor += s.len();
+        self.completion = None;
+    }
+
+    /// Deletes the character before the cursor
+    pub fn pop(&mut self) -> Option<char> {
+        let c = self.input[..self.cursor].chars().next_back()?;
+        self.cursor -= c.len_utf8();
+        self.input.remove(self.cursor);
+        self.completion = None;
+        Some(c)
+    }
+
+    /// Deletes the character at the cursor
+    pub fn delete(&mut self) {
+        if self.cursor < self.input.len() {
+            self.input.remove(self.cursor);
+            self.completion = None;
+        }
+    }
+
+    /// Deletes the word before the cursor
+    pub fn delete_word(&mut self) {
+        let start = self.input[..self.cursor]
+            .trim_end()
+            .rfind(char::is_whitespace)
+            .map(|idx| idx + 1)
+            .unwrap_or(0);
+        self.input.replace_range(start..self.cursor, "");
+        self.cursor = start;
+        self.completion = None;
+    }
+
+    /// Deletes everything before the cursor
+    pub fn delete_to_start(&mut self) {
+        self.input.replace_range(..self.cursor, "");
+        self.cursor = 0;
+        self.completion = None;
+    }
+
+    pub fn move_left(&mut self) {
+        if let Some(c) = self.input[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        if let Some(c) = self.input[self.cursor..].chars().next() {
+            self.cursor += c.len_utf8();
+        }
+    }
+
+    pub fn move_to_start(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_to_end(&mut self) {
+        self.cursor = self.input.len();
+    }
+
+    /// Replaces the input by the previous command of the history
+    pub fn history_previous(&mut self, history: &History) {
+        let position = match self.history_position {
+            None if history.entries.is_empty() => return,
+            None => {
+                self.draft = self.input.clone();
+                history.entries.len() - 1
+            }
+            Some(position) => position.saturating_sub(1),
+        };
+        self.history_position = Some(position);
+        self.set_input(history.entries[position].clone());
+    }
+
+    /// Replaces the input by the next command of the history, or by the
+    /// input before browsing the history
+    pub fn history_next(&mut self, history: &History) {
+        if let Some(position) = self.history_position {
+            if position + 1 < history.entries.len() {
+                self.history_position = Some(position + 1);
+                self.set_input(history.entries[position + 1].clone());
+            } else {
+                self.history_position = None;
+                let draft = std::mem::take(&mut self.draft);
+                self.set_input(draft);
+            }
+        }
+    }
+
+    /// Completes the word before the cursor. `candidates` returns the byte
+    /// offset where the word starts and all possible completions; it is only
+    /// called if the completion is not already in progress. Subsequent calls
+    /// cycle through the candidates.
+    pub fn complete(&mut self, candidates: impl FnOnce(&str) -> (usize, Vec<String>)) {
+        let completion = match self.completion.take() {
+            Some(mut completion) => {
+                completion.index = (completion.index + 1) % completion.candidates.len();
+                completion
+            }
+            None => {
+                let (start, candidates) = candidates(&self.input[..self.cursor]);
+                if candidates.is_empty() {
+                    return;
+                }
+                Completion {
+                    start,
+                    candidates,
+                    index: 0,
+                }
+            }
+        };
+        let candidate = &completion.candidates[completion.index];
+        self.input
+            .replace_range(completion.start..self.cursor, candidate);
+        self.cursor = completion.start + candidate.len();
+        self.completion = Some(completion);
+    }
+
+    fn set_input(&mut self, input: String) {
+        self.input = input;
+        self.cursor = self.input.len();
+        self.completion = None;
+    }
+
+    /// Position of the terminal cursor if the command line is rendered in
+    /// `area`
+    pub fn cursor_position(&self, area: Rect) -> (u16, u16) {
+        let offset = u16::try_from(self.input[..self.cursor].width() + 1).unwrap_or(u16::MAX);
+        (area.x + offset.min(area.width.saturating_sub(1)), area.y)
+    }
+}
+
+impl Widget for &CommandLine {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(Line::raw(format!(":{}", self.input))).render(area, buf);
+    }
+}
+
+/// Commands which have been executed, which are persisted across sessions
+#[derive(Debug, Default)]
+pub struct History {
+    entries: Vec<String>,
+    path: Option<PathBuf>,
+}
+
+impl History {
+    /// Reads the history from the local data directory of the user
+    pub fn load() -> Self {
+        let path = dirs::data_local_dir().map(|dir| dir.join("loghawk").join("history"));
+        let entries = path
+            .as_ref()
+            .and_then(|path| File::open(path).ok())
+            .map(|file| {
+                BufReader::new(file)
+                    .lines()
+                    .map_while(Result::ok)
+                    .filter(|line| !line.trim().is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { entries, path }
+    }
+
+    /// Adds a command to the history and stores it
+    pub fn add(&mut self, command: &str) -> anyhow::Result<()> {
+        let command = command.trim();
+        if command.is_empty() || self.entries.last().is_some_and(|last| last == command) {
+            return Ok(());
+        }
+        self.entries.push(command.to_string());
+
+        if let Some(path) = &self.path {
+            if self.entries.len() > MAX_HISTORY + HISTORY_SLACK {
+                self.entries.drain(..self.entries.len() - MAX_HISTORY);
+                std::fs::write(path, self.entries.join("\n") + "\n")?;
+            } else {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                writeln!(file, "{command}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CommandLine, History};
+
+    fn history_of(entries: &[&str]) -> History {
+        History {
+            entries: entries.iter().map(|entry| entry.to_string()).collect(),
+            path: None,
+        }
+    }
+
+    #[test]
+    fn test_cursor_movement() {
+        let mut line = CommandLine::default();
+        for c in "gö😀t".chars() {
+            line.push(c);
+        }
+        assert_eq!(line.cursor(), &"gö😀t".len());
+
+        // the cursor moves by characters, not by bytes
+        line.move_left();
+        line.move_left();
+        assert_eq!(line.cursor(), &"gö".len());
+        line.push('x');
+        assert_eq!(line.input(), "göx😀t");
+        line.move_right();
+        assert_eq!(line.pop(), Some('😀'));
+        assert_eq!(line.input(), "göxt");
+        line.delete();
+        assert_eq!(line.input(), "göx");
+        line.delete();
+        assert_eq!(line.input(), "göx");
+
+        line.move_to_start();
+        line.move_left();
+        assert_eq!(line.cursor(), &0);
+        assert_eq!(line.pop(), None);
+        line.move_right();
+        line.move_right();
+        assert_eq!(line.cursor(), &"gö".len());
+        line.delete_to_start();
+        assert_eq!(line.input(), "x");
+        line.move_to_end();
+        line.move_right();
+        assert_eq!(line.cursor(), &1);
+    }
+
+    #[test]
+    fn test_push_str() {
+        let mut line = CommandLine::with_input("open ".into());
+        line.push_str("/tmp/a\r\nb");
+        assert_eq!(line.input(), "open /tmp/a  b");
+        assert_eq!(line.cursor(), &line.input().len());
+    }
+
+    #[test]
+    fn test_delete_word() {
+        let mut line = CommandLine::with_input("filter user=älice  ".into());
+        line.delete_word();
+        assert_eq!(line.input(), "filter ");
+        assert_eq!(line.cursor(), &"filter ".len());
+        line.delete_word();
+        assert_eq!(line.input(), "");
+        line.delete_word();
+        assert_eq!(line.input(), "");
+
+        // only the part before the cursor is deleted
+        let mut line = CommandLine::with_input("set wrap".into());
+        line.move_left();
+        line.move_left();
+        line.delete_word();
+        assert_eq!(line.input(), "set ap");
+        assert_eq!(line.cursor(), &"set ".len());
+    }
+
+    #[test]
+    fn test_history() {
+        let history = history_of(&["quit", "set wrap"]);
+        let mut line = CommandLine::with_input("draft".into());
+        line.history_previous(&history);
+        assert_eq!(line.input(), "set wrap");
+        assert_eq!(line.cursor(), &"set wrap".len());
+        line.history_previous(&history);
+        assert_eq!(line.input(), "quit");
+        line.history_previous(&history);
+        assert_eq!(line.input(), "quit");
+        line.history_next(&history);
+        assert_eq!(line.input(), "set wrap");
+        line.history_next(&history);
+        assert_eq!(line.input(), "draft");
+        line.history_next(&history);
+        assert_eq!(line.input(), "draft");
+
+        let mut line = CommandLine::with_input("draft".into());
+        line.history_previous(&history_of(&[]));
+        assert_eq!(line.input(), "draft");
+    }
+
+    #[test]
+    fn test_completion() {
+        let candidates = |input: &str| {
+            assert_eq!(input, "set w");
+            (4, vec!["wrap".to_string(), "width".to_string()])
+        };
+        let mut line = CommandLine::with_input("set w".into());
+        line.complete(candidates);
+        assert_eq!(line.input(), "set wrap");
+        // the candidates are only searched once, and then cycled through
+        line.complete(|_| unreachable!());
+        assert_eq!(line.input(), "set width");
+        assert_eq!(line.cursor(), &"set width".len());
+        line.complete(|_| unreachable!());
+        assert_eq!(line.input(), "set wrap");
+
+        // editing the input ends the completion
+        line.push(' ');
+        line.complete(|input| (input.len(), Vec::new()));
+        assert_eq!(line.input(), "set wrap ");
+    }
+}
@@ -0,0 +1,539 @@
+use std::path::Path;
+
+use chrono::TimeDelta;
+use clap::ValueEnum;
+use getset::Getters;
+
+use crate::{
+    app::App, parse_duration, ExportFormat, ExportScope, RowFilter, SplitLayout, TimeRange,
+    TimestampDisplay, YankFormat,
+};
+
+/// gaps in the timeline which are larger than this are reported as anomaly
+const DEFAULT_GAP_THRESHOLD: TimeDelta = TimeDelta::hours(1);
+
+/// How the arguments of a command are completed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Argument {
+    None,
+    Column,
+    Path,
+    Option,
+    Text,
+}
+
+/// Description of a command which can be entered on the command line
+#[derive(Debug, Getters)]
+#[getset(get = "pub")]
+pub struct CommandInfo {
+    /// the first name is the main name, the others are abbreviations
+    names: &'static [&'static str],
+    arguments: &'static str,
+    description: &'static str,
+    argument: Argument,
+}
+
+const fn command(
+    names: &'static [&'static str],
+    arguments: &'static str,
+    description: &'static str,
+    argument: Argument,
+) -> CommandInfo {
+    CommandInfo {
+        names,
+        arguments,
+        description,
+        argument,
+    }
+}
+
+pub const COMMANDS: &[CommandInfo] = &[
+    command(&["quit", "q"], "", "quit loghawk", Argument::None),
+    command(
+        &["open", "o"],
+        "<path>",
+        "open a file in a new tab",
+        Argument::Path,
+    ),
+    command(&["goto"], "<row>", "go to a row", Argument::Text),
+    command(
+        &["filter"],
+        "<column>=<value>",
+        "only display rows with this value",
+        Argument::Column,
+    ),
+    command(
+        &["grep"],
+        "<text>",
+        "only display rows which contain the text",
+        Argument::Text,
+    ),
+    command(
+        &["tagged"],
+        "<tag>",
+        "only display rows with this tag",
+        Argument::Text,
+    ),
+    command(
+        &["range"],
+        "[<from> <to>]",
+        "only display rows in a time range",
+        Argument::Text,
+    ),
+    command(
+        &["nofilter"],
+        "",
+        "remove all filters except the time range",
+        Argument::None,
+    ),
+    command(
+        &["anomalies"],
+        "[off|<threshold>]",
+        "find gaps, reordered and duplicate rows",
+        Argument::Text,
+    ),
+    command(
+        &["stats"],
+        "[<column>]",
+        "display statistics of a column",
+        Argument::Column,
+    ),
+    command(
+        &["export"],
+        "<path> [visible|selection|bookmarks]",
+        "write rows to a file",
+        Argument::Path,
+    ),
+    command(
+        &["yankformat"],
+        "<tsv|csv|raw>",
+        "set the format of yanked rows, same as set yank-format",
+        Argument::Text,
+    ),
+    command(
+        &["set"],
+        "[<option>[=<value>]]",
+        "change or display options",
+        Argument::Option,
+    ),
+    command(
+        &["split", "sp"],
+        "",
+        "display two tabs above each other",
+        Argument::None,
+    ),
+    command(
+        &["vsplit", "vs"],
+        "",
+        "display two tabs side by side",
+        Argument::None,
+    ),
+    command(&["only"], "", "display only the active tab", Argument::None),
+    command(
+        &["sync"],
+        "",
+        "keep both panes at the same time",
+        Argument::None,
+    ),
+    command(
+        &["note"],
+        "[<text>]",
+        "annotate the current row",
+        Argument::Text,
+    ),
+    command(&["tag"], "<tag>...", "tag the current row", Argument::Text),
+    command(
+        &["untag"],
+        "<tag>...",
+        "remove tags from the current row",
+        Argument::Text,
+    ),
+    command(&["annotations"], "", "list annotated rows", Argument::None),
+    command(
+        &["problems"],
+        "",
+        "list rows which could not be parsed properly",
+        Argument::None,
+    ),
+    command(
+        &["suspicious"],
+        "",
+        "list values with invisible characters or look-alike words",
+        Argument::None,
+    ),
+    command(
+        &["bookmarks", "marks"],
+        "",
+        "list bookmarks and marks",
+        Argument::None,
+    ),
+];
+
+/// Options which can be changed with `:set`
+const OPTIONS: &[&str] = &[
+    "mask-unicode",
+    "sync",
+    "timeline",
+    "timestamps",
+    "wrap",
+    "yank-format",
+];
+
+pub fn find_command(name: &str) -> Option<&'static CommandInfo> {
+    COMMANDS.iter().find(|info| info.names.contains(&name))
+}
+
+/// Executes a command which has been entered on the command line
+pub fn execute(app: &mut App, command: &str) -> anyhow::Result<()> {
+    let (name, args) = split_command(command);
+    if name.is_empty() {
+        return Ok(());
+    }
+    let info = find_command(name).ok_or_else(|| anyhow::anyhow!("unknown command: {name}"))?;
+    let usage = || anyhow::anyhow!("usage: {} {}", info.names[0], info.arguments);
+
+    match info.names[0] {
+        "quit" => app.quit(),
+        "open" => {
+            if args.is_empty() {
+                return Err(usage());
+            }
+            app.open(args)?;
+        }
+        "goto" => {
+            let row: usize = args.parse().map_err(|_| usage())?;
+            app.goto(row.saturating_sub(1));
+        }
+        "filter" => {
+            let (column_name, value) = args.split_once('=').ok_or_else(usage)?;
+            let column_name = column_name.trim().to_string();
+            let column = app
+                .tab()
+                .data()
+                .find_column(&column_name)
+                .ok_or_else(|| anyhow::anyhow!("unknown column: {column_name}"))?;
+            app.add_filter(RowFilter::ColumnEquals {
+                column,
+                column_name,
+                value: value.to_string(),
+            });
+        }
+        "grep" => {
+            if args.is_empty() {
+                return Err(usage());
+            }
+            app.add_filter(RowFilter::Contains(args.to_string()));
+        }
+        "tagged" => {
+            if args.is_empty() {
+                return Err(usage());
+            }
+            app.add_filter(RowFilter::Tag(args.to_string()));
+        }
+        "range" => {
+            let range = if args.is_empty() {
+                None
+            } else {
+                Some(TimeRange::parse(args, *app.cli().keep_unparsed())?)
+            };
+            app.set_time_range(range);
+        }
+        "nofilter" => app.clear_filters(),
+        "anomalies" => match args {
+            "off" => app.tab_mut().set_anomalies(Vec::new()),
+            "" => app.find_anomalies(DEFAULT_GAP_THRESHOLD),
+            threshold => app.find_anomalies(parse_duration(threshold)?),
+        },
+        "stats" => {
+            let column = if args.is_empty() {
+                app.current_column()
+            } else {
+                app.tab()
+                    .data()
+                    .find_column(args)
+                    .ok_or_else(|| anyhow::anyhow!("unknown column: {args}"))?
+            };
+            app.compute_column_stats(column);
+        }
+        "export" => {
+            let (path, scope) = match args.rsplit_once(char::is_whitespace) {
+                Some((path, scope)) => match ExportScope::from_str(scope, true) {
+                    Ok(scope) => (path.trim(), scope),
+                    Err(_) => (args, ExportScope::default()),
+                },
+                None => (args, ExportScope::default()),
+            };
+            if path.is_empty() {
+                return Err(usage());
+            }
+            let path = Path::new(path);
+            let format = ExportFormat::from_path(path).unwrap_or(ExportFormat::Csv);
+            let rows = app.export(path, format, scope)?;
+            app.set_message(Some(format!("exported {rows} rows to {}", path.display())));
+        }
+        "yankformat" => {
+            let format = YankFormat::from_str(args, true)
+                .map_err(|_| anyhow::anyhow!("usage: yankformat <tsv|csv|raw>"))?;
+            app.set_yank_format(format);
+        }
+        "set" => set_option(app, args)?,
+        "split" => app.split(SplitLayout::Horizontal)?,
+        "vsplit" => app.split(SplitLayout::Vertical)?,
+        "only" => app.split(SplitLayout::Single)?,
+        "sync" => app.toggle_sync_by_time(),
+        "note" => app.annotate(|annotations, record| {
+            annotations.set_note(record, args.to_string());
+        })?,
+        "tag" | "untag" => {
+            if args.is_empty() {
+                return Err(usage());
+            }
+            let add = info.names[0] == "tag";
+            app.annotate(|annotations, record| {
+                for tag in args.split_whitespace() {
+                    if add {
+                        annotations.add_tag(record, tag.to_string());
+                    } else {
+                        annotations.remove_tag(record, tag);
+                    }
+                }
+            })?;
+        }
+        "annotations" => app.show_annotations(),
+        "problems" => app.show_parse_problems(),
+        "suspicious" => app.show_suspicious_values(),
+        "bookmarks" => app.show_bookmarks(),
+        _ => unreachable!("command without implementation: {name}"),
+    }
+    Ok(())
+}
+
+/// Splits a command into its name and its arguments
+fn split_command(command: &str) -> (&str, &str) {
+    let command = command.trim();
+    command
+        .split_once(char::is_whitespace)
+        .map(|(name, args)| (name, args.trim()))
+        .unwrap_or((command, ""))
+}
+
+/// Handles `:set`, which accepts `option`, `nooption` and `option=value`.
+/// Without arguments, the current values of all options are displayed.
+fn set_option(app: &mut App, args: &str) -> anyhow::Result<()> {
+    if args.is_empty() {
+        let values = OPTIONS
+            .iter()
+            .map(|option| format!("{option}={}", option_value(app, option)))
+            .collect::<Vec<_>>();
+        app.set_message(Some(values.join(" ")));
+        return Ok(());
+    }
+
+    let (option, value) = parse_option(args);
+    let flag = || parse_flag(option, value);
+
+    match option {
+        "mask-unicode" => {
+            app.set_mask_unicode(flag()?);
+        }
+        "sync" => {
+            if flag()? != *app.sync_by_time() {
+                app.toggle_sync_by_time();
+            }
+        }
+        "timeline" => {
+            app.set_show_timeline(flag()?);
+        }
+        "wrap" => {
+            app.set_wrap(flag()?);
+        }
+        "timestamps" => {
+            let display = match value {
+                Some("original") => TimestampDisplay::Original,
+                Some("utc") => TimestampDisplay::Utc,
+                Some("tz") => match app.cli().timezone() {
+                    Some(tz) => TimestampDisplay::Timezone(*tz),
+                    None => anyhow::bail!("no timezone has been set with --tz"),
+                },
+                _ => anyhow::bail!("usage: set timestamps=<original|utc|tz>"),
+            };
+            app.set_timestamp_display(display);
+        }
+        "yank-format" => {
+            let format = value
+                .and_then(|value| YankFormat::from_str(value, true).ok())
+                .ok_or_else(|| anyhow::anyhow!("usage: set yank-format=<tsv|csv|raw>"))?;
+            app.set_yank_format(format);
+        }
+        _ => anyhow::bail!("unknown option: {option}"),
+    }
+    Ok(())
+}
+
+/// Splits the arguments of `:set` into the option and its value, if any.
+/// `nooption` is the same as `option=false`.
+fn parse_option(args: &str) -> (&str, Option<&str>) {
+    match args.split_once('=') {
+        Some((option, value)) => (option.trim(), Some(value.trim())),
+        None => match args.strip_prefix("no") {
+            Some(option) if OPTIONS.contains(&option) => (option, Some("false")),
+            _ => (args, None),
+        },
+    }
+}
+
+/// Parses the value of an option which can be switched on and off; it is
+/// switched on if there is no value
+fn parse_flag(option: &str, value: Option<&str>) -> anyhow::Result<bool> {
+    match value {
+        None | Some("true") | Some("on") => Ok(true),
+        Some("false") | Some("off") => Ok(false),
+        Some(value) => anyhow::bail!("invalid value for {option}: {value}"),
+    }
+}
+
+fn option_value(app: &App, option: &str) -> String {
+    match option {
+        "mask-unicode" => app.mask_unicode().to_string(),
+        "sync" => app.sync_by_time().to_string(),
+        "timeline" => app.show_timeline().to_string(),
+        "wrap" => app.wrap().to_string(),
+        "timestamps" => match app.timestamp_display() {
+            TimestampDisplay::Original => "original".into(),
+            TimestampDisplay::Utc => "utc".into(),
+            TimestampDisplay::Timezone(_) => "tz".into(),
+        },
+        "yank-format" => format!("{:?}", app.yank_format()).to_lowercase(),
+        _ => String::new(),
+    }
+}
+
+/// Returns the byte offset of the word before the cursor, together with all
+/// completions of this word
+pub fn complete(app: &App, input: &str) -> (usize, Vec<String>) {
+    let start = input
+        .rfind(char::is_whitespace)
+        .map(|idx| idx + 1)
+        .unwrap_or(0);
+    let word = &input[start..];
+    let matching = |candidates: Vec<String>| -> Vec<String> {
+        candidates
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(word))
+            .collect()
+    };
+
+    if start == 0 {
+        return (
+            start,
+            matching(
+                COMMANDS
+                    .iter()
+                    .map(|info| info.names[0].to_string())
+                    .collect(),
+            ),
+        );
+    }
+
+    let name = input.split_whitespace().next().unwrap_or_default();
+    let candidates = match find_command(name).map(|info| info.argument) {
+        Some(Argument::Column) => {
+            let data = app.tab().data();
+            matching(
+                (0..data.column_count())
+                    .filter_map(|column| data.column_info(column))
+                    .map(|info| info.name().clone())
+                    .collect(),
+            )
+        }
+        Some(Argument::Option) => matching(OPTIONS.iter().map(|o| o.to_string()).collect()),
+        Some(Argument::Path) => complete_path(word),
+        _ => Vec::new(),
+    };
+    (start, candidates)
+}
+
+/// Completes paths of files and directories; directories end with a slash
+fn complete_path(word: &str) -> Vec<String> {
+    let (directory, prefix) = match word.rfind('/') {
+        Some(idx) => (&word[..=idx], &word[idx + 1..]),
+        None => ("", word),
+    };
+    let entries = match std::fs::read_dir(if directory.is_empty() { "." } else { directory }) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut candidates: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !name.starts_with(prefix) || (prefix.is_empty() && name.starts_with('.')) {
+                return None;
+            }
+            let suffix = if entry.path().is_dir() { "/" } else { "" };
+            Some(format!("{directory}{name}{suffix}"))
+        })
+        .collect();
+    candidates.sort();
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_command, parse_flag, parse_option, split_command, COMMANDS};
+
+    #[test]
+    fn test_split_command() {
+        assert_eq!(split_command("  quit "), ("quit", ""));
+        assert_eq!(
+            split_command("filter  user = alice "),
+            ("filter", "user = alice")
+        );
+        assert_eq!(split_command("open\t/tmp/x.csv"), ("open", "/tmp/x.csv"));
+        assert_eq!(split_command(""), ("", ""));
+    }
+
+    #[test]
+    fn test_find_command() {
+        assert_eq!(find_command("q").unwrap().names()[0], "quit");
+        assert_eq!(find_command("o").unwrap().names()[0], "open");
+        assert!(find_command("Quit").is_none());
+        assert!(find_command("").is_none());
+
+        // every name and abbreviation must belong to exactly one command
+        for info in COMMANDS {
+            for name in info.names().iter() {
+                let matching = COMMANDS
+                    .iter()
+                    .filter(|other| other.names().contains(name))
+                    .count();
+                assert_eq!(matching, 1, "command name '{name}'");
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_option() {
+        assert_eq!(parse_option("wrap"), ("wrap", None));
+        assert_eq!(parse_option("nowrap"), ("wrap", Some("false")));
+        assert_eq!(parse_option("wrap = off"), ("wrap", Some("off")));
+        assert_eq!(
+            parse_option("yank-format=csv"),
+            ("yank-format", Some("csv"))
+        );
+        // only known options can be negated
+        assert_eq!(parse_option("nothing"), ("nothing", None));
+    }
+
+    #[test]
+    fn test_parse_flag() {
+        for value in [None, Some("true"), Some("on")] {
+            assert!(parse_flag("wrap", value).unwrap(), "{value:?}");
+        }
+        for value in [Some("false"), Some("off")] {
+            assert!(!parse_flag("wrap", value).unwrap(), "{value:?}");
+        }
+        let why = parse_flag("wrap", Some("yes")).unwrap_err();
+        assert_eq!(why.to_string(), "invalid value for wrap: yes");
+    }
+}
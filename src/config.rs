@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use getset::Getters;
+use serde::Deserialize;
+
+use crate::KeymapConfig;
+
+/// Settings which are read from `loghawk/config.toml` in the configuration
+/// directory of the user
+#[derive(Debug, Default, Deserialize, Getters)]
+#[serde(default, deny_unknown_fields)]
+#[getset(get = "pub")]
+pub struct Config {
+    keys: KeymapConfig,
+}
+
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("loghawk").join("config.toml"))
+    }
+
+    /// Reads the configuration file, if there is one
+    pub fn load() -> anyhow::Result<Self> {
+        match Self::path() {
+            Some(path) if path.exists() => {
+                let contents = std::fs::read_to_string(&path)
+                    .with_context(|| format!("failed to read {}", path.display()))?;
+                toml::from_str(&contents)
+                    .with_context(|| format!("invalid configuration in {}", path.display()))
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+}
@@ -1,29 +1,31 @@
 use anyhow::bail;
 use clio::ClioPath;
+use csv::StringRecord;
 use ratatui::{
     text::Line,
     widgets::{Cell, ListItem, Row},
 };
-use std::fmt::Debug;
+use std::{fmt::Debug, ops::Range};
 
 use crate::{
-    log_line::LogLine, AsMasked, ColumnInfo, ColumnWidth, DataRows,
-    DataWidths, IndexRows, InputReader, IterDataColumns, LogData, ViewPort,
+    log_line::LogLine, read_decoded, timestamp, AsMasked, ColumnInfo, ColumnWidth, DataRows,
+    DataWidths, IndexRows, IterDataColumns, LogData, ParseProblem, RawInput, TimestampDisplay,
+    ViewPort,
 };
 
 pub struct CsvData {
     records: Vec<LogLine>,
     columns: Vec<ColumnInfo>,
+    raw_input: RawInput,
+    problems: Vec<ParseProblem>,
 }
 
 impl CsvData {
     fn find_start(&self, viewport: &ViewPort) -> (usize, usize) {
-        assert!(self.columns.len() >= 2);
-
         let mut skip = *viewport.hoffset();
         for (idx, width) in self
             .iter_data_columns()
-            .map(|c| usize::try_from(*c.width()).unwrap())
+            .map(|c| usize::try_from(*c.width()).unwrap_or_default())
             .enumerate()
         {
             if skip < width {
@@ -32,13 +34,21 @@ impl CsvData {
                 skip -= width;
             }
         }
-        (self.columns.len() - 1, skip)
+        (self.columns.len().saturating_sub(1), skip)
     }
 }
 
 impl LogData for CsvData {
+    fn format_name(&self) -> &'static str {
+        "CSV"
+    }
+
+    fn raw_input(&self) -> &RawInput {
+        &self.raw_input
+    }
+
     fn data_columns(&self) -> usize {
-        self.columns.len()
+        self.columns.len().saturating_sub(1)
     }
 
     fn data_infos(&self, idx: usize) -> Option<&ColumnInfo> {
@@ -50,15 +60,11 @@ impl LogData for CsvData {
         DataWidths::from(
             self.iter_data_columns()
                 .skip(first_column_index)
-                .map(|c| usize::try_from(*c.width()).unwrap())
+                .map(|c| usize::try_from(*c.width()).unwrap_or_default())
                 .enumerate()
                 .map(move |(idx, width)| {
                     if idx == 0 {
-                        if width > skip_in_column {
-                            width - skip_in_column
-                        } else {
-                            0
-                        }
+                        width.saturating_sub(skip_in_column)
                     } else {
                         width
                     }
@@ -66,25 +72,31 @@ impl LogData for CsvData {
         )
     }
 
-    fn data_rows(&self, viewport: &ViewPort, mask_unicode: bool) -> DataRows<'_> {
+    fn data_rows<'d>(
+        &'d self,
+        rows: &'d [usize],
+        viewport: &ViewPort,
+        mask_unicode: bool,
+    ) -> DataRows<'d> {
         let (first_column_index, skip_in_column) = self.find_start(viewport);
 
-        let upper_bound = usize::min(self.records.len(), viewport.vend());
         DataRows::from(
-            self.records[viewport.vbegin()..upper_bound]
-                .iter()
+            rows.iter()
+                .filter_map(|idx| self.records.get(*idx))
                 .map(move |r| {
 
                     let row = Row::new(r.iter_contents().skip(first_column_index).enumerate().map(
                         |(idx, value)| {
+                            // the index column is column 0 of the record
+                            let homoglyphs = r.homoglyphs(first_column_index + idx + 1);
                             Cell::new(if idx == 0 {
                                 if skip_in_column >= value.len() {
                                     Line::raw("")
                                 } else {
-                                    value.as_masked(skip_in_column.., mask_unicode)
+                                    value.as_masked(skip_in_column.., mask_unicode, homoglyphs)
                                 }
                             } else {
-                                value.as_masked(.., mask_unicode)
+                                value.as_masked(.., mask_unicode, homoglyphs)
                             })
                         },
                     ));
@@ -94,12 +106,16 @@ impl LogData for CsvData {
         )
     }
 
-    fn index_rows(&self, viewport: &ViewPort, mask_unicode: bool) -> IndexRows<'_> {
-        let upper_bound = usize::min(self.records.len(), viewport.vend());
+    fn index_rows<'d>(
+        &'d self,
+        rows: &'d [usize],
+        mask_unicode: bool,
+        timestamp_display: TimestampDisplay,
+    ) -> IndexRows<'d> {
         IndexRows::from(
-            self.records[viewport.vbegin()..upper_bound]
-                .iter()
-                .map(move |r| ListItem::new(r.key_value().as_masked(.., mask_unicode))),
+            rows.iter()
+                .filter_map(|idx| self.records.get(*idx))
+                .map(move |r| ListItem::new(timestamp_display.index_line(r, mask_unicode))),
         )
     }
 
@@ -107,10 +123,18 @@ impl LogData for CsvData {
         self.records.len()
     }
 
+    fn parse_problems(&self) -> &[ParseProblem] {
+        &self.problems
+    }
+
     fn is_empty(&self) -> bool {
         self.records.is_empty()
     }
 
+    fn line(&self, idx: usize) -> Option<&LogLine> {
+        self.records.get(idx)
+    }
+
     fn iter_data_columns(&self) -> IterDataColumns<'_> {
         IterDataColumns::from(self.columns.iter().skip(1))
     }
@@ -123,26 +147,50 @@ impl LogData for CsvData {
 impl TryFrom<&ClioPath> for CsvData {
     type Error = anyhow::Error;
 
+    /// Parses the file tolerantly: rows may have more or less fields than
+    /// the header, and lines which cannot be parsed are kept as raw text.
     fn try_from(path: &ClioPath) -> Result<Self, Self::Error> {
-        let mut reader = csv::Reader::from_reader(InputReader::try_from(path)?);
-        let mut records = Vec::new();
-        let mut columns = Vec::new();
+        let (text, raw_input) = read_decoded(path)?;
+        let mut parser = TolerantParser::default();
+        parser.parse(&text);
+        let TolerantParser {
+            headers,
+            mut records,
+            ranges,
+            widths,
+            mut problems,
+        } = parser;
 
-        for record in reader.records() {
-            let record = record?;
-
-            if columns.is_empty() {
-                columns = vec![ColumnWidth::Width(0); record.iter().count()];
-            } else {
-                for (idx, s) in record.iter().enumerate() {
-                    columns[idx].advance_to(s.len());
-                }
+        // the parser knows offsets in the text, which are only converted to
+        // offsets in the file if the text has the same characters
+        let mut positions = raw_input.positions(&text);
+        let lossy = raw_input.is_lossy();
+        if !lossy {
+            for (record, range) in records.iter_mut().zip(ranges) {
+                record.set_position(Some(positions.position(range.start, range.end)));
             }
-            let line = LogLine::try_from(record)?;
-            records.push(line);
+        }
+        for problem in problems.iter_mut() {
+            let byte = problem
+                .byte()
+                .filter(|_| !lossy)
+                .map(|byte| positions.offset(usize::try_from(byte).unwrap_or(usize::MAX)));
+            problem.set_byte(byte);
         }
 
-        let columns: Vec<_> = columns.into_iter().map(ColumnInfo::new).collect();
+        timestamp::assign_timestamps(&mut records);
+
+        let columns: Vec<_> = widths
+            .into_iter()
+            .enumerate()
+            .map(|(idx, width)| {
+                let name = headers
+                    .get(idx)
+                    .cloned()
+                    .unwrap_or_else(|| format!("column {}", idx + 1));
+                ColumnInfo::new(name, width)
+            })
+            .collect();
         if columns.len() < 2 {
             if columns.len() == 1 {
                 bail!("found a key column, but no data columns");
@@ -154,10 +202,178 @@ impl TryFrom<&ClioPath> for CsvData {
         Ok(Self {
             records,
             columns,
+            raw_input,
+            problems,
         })
     }
 }
 
+/// Reads CSV records and recovers from malformed input
+#[derive(Default)]
+struct TolerantParser {
+    headers: Vec<String>,
+    records: Vec<LogLine>,
+
+    /// where the records have been found in the decoded text, without line
+    /// breaks
+    ranges: Vec<Range<usize>>,
+    widths: Vec<ColumnWidth>,
+    problems: Vec<ParseProblem>,
+}
+
+impl TolerantParser {
+    fn parse(&mut self, text: &str) {
+        // byte offset and line number where parsing (re)starts
+        let mut offset = 0;
+        let mut line = 1;
+        while let Some((next_offset, next_line)) = self.parse_from(text, offset, line) {
+            offset = next_offset;
+            line = next_line;
+        }
+    }
+
+    /// Parses records starting at `offset`, which is in line `line`. If a
+    /// line cannot be parsed, it is added as raw text, and the position of
+    /// the following line is returned to continue parsing there.
+    fn parse_from(&mut self, text: &str, offset: usize, line: u64) -> Option<(usize, u64)> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(&text.as_bytes()[offset..]);
+        let mut record = StringRecord::new();
+        loop {
+            let start = offset + usize::try_from(reader.position().byte()).unwrap_or_default();
+            let start_line = line + reader.position().line() - 1;
+            let lines_before = reader.position().line();
+            let problem = match reader.read_record(&mut record) {
+                Ok(false) => return None,
+                Ok(true) => {
+                    let end =
+                        offset + usize::try_from(reader.position().byte()).unwrap_or_default();
+                    let raw = &text[start..end.min(text.len())];
+
+                    // the line break which terminates the record is counted
+                    // as well
+                    let lines = reader.position().line() - lines_before;
+                    let multiline = lines > u64::from(raw.ends_with('\n'));
+
+                    // an unterminated quote makes the reader consume the
+                    // following lines, up to the next quote or to the end
+                    // of the file
+                    if (multiline || end >= text.len()) && has_broken_quotes(raw) {
+                        "unterminated quoted field".to_string()
+                    } else {
+                        let raw = raw.trim_end_matches(['\r', '\n']);
+                        self.add_record(&record, raw, start_line, start);
+                        continue;
+                    }
+                }
+                Err(why) => why.to_string(),
+            };
+
+            let line_end = text[start..]
+                .find('\n')
+                .map(|idx| start + idx + 1)
+                .unwrap_or(text.len());
+            let raw = text[start..line_end].trim_end_matches(['\r', '\n']);
+            self.add_problem(start_line, start, problem);
+            self.add_raw_line(raw, start);
+            return (line_end < text.len()).then_some((line_end, start_line + 1));
+        }
+    }
+
+    /// Adds a record, which has been read from `raw` at byte `start` of the
+    /// text
+    fn add_record(&mut self, record: &StringRecord, raw: &str, line: u64, start: usize) {
+        if self.headers.is_empty() {
+            self.headers = record.iter().map(String::from).collect();
+            return;
+        }
+        if record.len() != self.headers.len() {
+            self.add_problem(
+                line,
+                start,
+                format!(
+                    "expected {} fields, but found {}",
+                    self.headers.len(),
+                    record.len()
+                ),
+            );
+        }
+        for (idx, value) in record.iter().enumerate() {
+            self.advance_width(idx, value.len());
+        }
+        match LogLine::try_from(record.clone()) {
+            Ok(line) => {
+                self.records.push(line);
+                self.ranges.push(start..start + raw.len());
+            }
+            Err(why) => {
+                self.add_problem(line, start, why.to_string());
+                self.add_raw_line(raw, start);
+            }
+        }
+    }
+
+    /// Adds a line which could not be parsed as a record without key. The
+    /// line starts at byte `start` of the text.
+    fn add_raw_line(&mut self, raw: &str, start: usize) {
+        // the line is displayed across the data area, so that it does not
+        // widen any column
+        self.advance_width(0, 0);
+        self.advance_width(1, 0);
+        self.records.push(LogLine::unparsed(raw.to_string()));
+        self.ranges.push(start..start + raw.len());
+    }
+
+    fn add_problem(&mut self, line: u64, byte: usize, message: String) {
+        self.problems.push(ParseProblem::new(
+            self.records.len(),
+            line,
+            u64::try_from(byte).ok(),
+            message,
+        ));
+    }
+
+    /// Widens a column, adding it if there are not as many columns yet
+    fn advance_width(&mut self, column: usize, width: usize) {
+        if self.widths.len() <= column {
+            self.widths.resize(column + 1, ColumnWidth::Width(0));
+        }
+        self.widths[column].advance_to(width);
+    }
+}
+
+/// Returns `true` if a quoted field of a record is not terminated, or if its
+/// closing quote is not followed by the end of the field. Both happen if a
+/// quote is missing, because the reader then takes everything up to the next
+/// quote as part of the field.
+fn has_broken_quotes(raw: &str) -> bool {
+    let mut chars = raw.chars().peekable();
+    let mut field_start = true;
+    while let Some(c) = chars.next() {
+        if field_start && c == '"' {
+            loop {
+                match chars.next() {
+                    None => return true,
+                    Some('"') if chars.peek() == Some(&'"') => {
+                        chars.next();
+                    }
+                    Some('"') => break,
+                    Some(_) => (),
+                }
+            }
+            if !matches!(chars.peek(), None | Some(',' | '\r' | '\n')) {
+                return true;
+            }
+            field_start = false;
+        } else {
+            field_start = c == ',';
+        }
+    }
+    false
+}
+
 impl Debug for CsvData {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("CsvData")
@@ -165,3 +381,111 @@ impl Debug for CsvData {
             .finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::TolerantParser;
+
+    /// name of the case, input, expected records and expected problems
+    type Case<'a> = (&'a str, &'a str, &'a [&'a [&'a str]], &'a [(u64, &'a str)]);
+
+    /// Parses `text` and returns the values of all records, with the key
+    /// first, and the line numbers and messages of all problems
+    fn parse(text: &str) -> (Vec<Vec<String>>, Vec<(u64, String)>) {
+        let mut parser = TolerantParser::default();
+        parser.parse(text);
+        let records = parser
+            .records
+            .iter()
+            .map(|record| {
+                std::iter::once(record.key_value())
+                    .chain(record.iter_contents())
+                    .map(String::from)
+                    .collect()
+            })
+            .collect();
+        let problems = parser
+            .problems
+            .iter()
+            .map(|problem| (*problem.line(), problem.message().clone()))
+            .collect();
+        (records, problems)
+    }
+
+    #[test]
+    fn test_tolerant_parser() {
+        let expected_fields = "expected 3 fields, but found";
+        let unterminated = "unterminated quoted field";
+        let cases: &[Case] = &[
+            (
+                "well-formed",
+                "k,a,b\n1,x,y\n2,\"multi\nline\",z\n",
+                &[&["1", "x", "y"], &["2", "multi\nline", "z"]],
+                &[],
+            ),
+            (
+                "short row",
+                "k,a,b\n1,x\n2,x,y\n",
+                &[&["1", "x"], &["2", "x", "y"]],
+                &[(2, expected_fields)],
+            ),
+            (
+                "long row",
+                "k,a,b\n1,x,y,z\n2,x,y\n",
+                &[&["1", "x", "y", "z"], &["2", "x", "y"]],
+                &[(2, expected_fields)],
+            ),
+            (
+                "broken quote mid-file",
+                "k,a,b\n1,\"x,y\n2,x,y\n3,\"z\",w\n",
+                &[&["", "1,\"x,y"], &["2", "x", "y"], &["3", "z", "w"]],
+                &[(2, unterminated)],
+            ),
+            (
+                "broken quote at end of file",
+                "k,a,b\n1,x,y\n2,\"x,y\n3,x,y",
+                &[&["1", "x", "y"], &["", "2,\"x,y"], &["3", "x", "y"]],
+                &[(3, unterminated)],
+            ),
+            (
+                "broken quote in last line",
+                "k,a,b\n1,x,y\n2,\"x,y",
+                &[&["1", "x", "y"], &["", "2,\"x,y"]],
+                &[(3, unterminated)],
+            ),
+        ];
+
+        for (name, text, expected_records, expected_problems) in cases {
+            let (records, problems) = parse(text);
+            assert_eq!(records, *expected_records, "records of '{name}'");
+            assert_eq!(
+                problems.len(),
+                expected_problems.len(),
+                "problems of '{name}': {problems:?}"
+            );
+            for ((line, message), (expected_line, expected_message)) in
+                problems.iter().zip(expected_problems.iter())
+            {
+                assert_eq!(line, expected_line, "line of problem in '{name}'");
+                assert!(
+                    message.starts_with(expected_message),
+                    "problem in '{name}': {message}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_unparsed_line_keeps_widths() {
+        let mut parser = TolerantParser::default();
+        parser.parse("k,a,b\n1,xy,z\n2,\"a very long broken line,z\n");
+        let widths: Vec<_> = parser
+            .widths
+            .iter()
+            .map(|width| usize::try_from(*width).unwrap())
+            .collect();
+        assert_eq!(widths, [1, 2, 1]);
+        assert!(!parser.records[0].is_unparsed());
+        assert!(parser.records[1].is_unparsed());
+    }
+}
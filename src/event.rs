@@ -5,7 +5,7 @@ use std::thread;
 use std::time::{Duration, Instant};
 
 /// Terminal events.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub enum Event {
     /// Terminal tick.
     Tick,
@@ -15,6 +15,10 @@ pub enum Event {
     Mouse(MouseEvent),
     /// Terminal resize.
     Resize(u16, u16),
+    /// Pasted text.
+    Paste(String),
+    /// The terminal cannot be read anymore.
+    Error(String),
 }
 
 /// Terminal event handler.
@@ -43,26 +47,42 @@ impl EventHandler {
                         .checked_sub(last_tick.elapsed())
                         .unwrap_or(tick_rate);
 
-                    if event::poll(timeout).expect("failed to poll new events") {
-                        match event::read().expect("unable to read event") {
-                            CrosstermEvent::Key(e) => {
-                                if e.kind == KeyEventKind::Press {
-                                    sender.send(Event::Key(e))
-                                } else {
-                                    Ok(())
-                                }
-                            }
-                            CrosstermEvent::Mouse(e) => sender.send(Event::Mouse(e)),
-                            CrosstermEvent::Resize(w, h) => sender.send(Event::Resize(w, h)),
-                            CrosstermEvent::FocusGained => Ok(()),
-                            CrosstermEvent::FocusLost => Ok(()),
-                            CrosstermEvent::Paste(_) => unimplemented!(),
+                    let event = match event::poll(timeout).and_then(|ready| {
+                        if ready {
+                            event::read().map(Some)
+                        } else {
+                            Ok(None)
                         }
-                        .expect("failed to send terminal event")
+                    }) {
+                        Ok(event) => event,
+                        Err(why) => {
+                            // there will be no more events, so the user
+                            // interface has to be closed
+                            let _ = sender.send(Event::Error(why.to_string()));
+                            break;
+                        }
+                    };
+
+                    let sent = match event {
+                        Some(CrosstermEvent::Key(e)) if e.kind == KeyEventKind::Press => {
+                            sender.send(Event::Key(e))
+                        }
+                        Some(CrosstermEvent::Mouse(e)) => sender.send(Event::Mouse(e)),
+                        Some(CrosstermEvent::Resize(w, h)) => sender.send(Event::Resize(w, h)),
+                        Some(CrosstermEvent::Paste(text)) => sender.send(Event::Paste(text)),
+                        _ => Ok(()),
+                    };
+
+                    // if the receiver has been dropped, nobody is interested
+                    // in events anymore
+                    if sent.is_err() {
+                        break;
                     }
 
                     if last_tick.elapsed() >= tick_rate {
-                        sender.send(Event::Tick).expect("failed to send tick event");
+                        if sender.send(Event::Tick).is_err() {
+                            break;
+                        }
                         last_tick = Instant::now();
                     }
                 }
@@ -0,0 +1,276 @@
+use std::{cmp::Ordering, io::Write, path::Path};
+
+use clap::ValueEnum;
+use unicode_width::UnicodeWidthStr;
+
+use crate::{parse_timestamp, Annotation, LogData, Tab};
+
+/// File formats which the rows of a tab can be exported to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// columns aligned with spaces
+    Text,
+    Csv,
+    Jsonl,
+    Markdown,
+    Html,
+}
+
+impl ExportFormat {
+    /// Guesses the format from the extension of the target file
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_string_lossy().to_lowercase();
+        match &extension[..] {
+            "txt" => Some(Self::Text),
+            "csv" => Some(Self::Csv),
+            "jsonl" | "ndjson" | "json" => Some(Self::Jsonl),
+            "md" | "markdown" => Some(Self::Markdown),
+            "html" | "htm" => Some(Self::Html),
+            _ => None,
+        }
+    }
+}
+
+/// Rows which are exported
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ExportScope {
+    /// all rows which match the filters
+    #[default]
+    Visible,
+
+    /// the selected rows, or the current row if nothing is selected
+    Selection,
+
+    /// visible rows which are bookmarked or marked
+    Bookmarks,
+}
+
+impl ExportScope {
+    /// Indices of the records of the tab which are exported
+    pub fn rows(&self, tab: &Tab) -> Vec<usize> {
+        match self {
+            Self::Visible => tab.visible_rows().clone(),
+            Self::Selection => tab.selected_rows().to_vec(),
+            Self::Bookmarks => {
+                let records = tab.bookmarks().records();
+                tab.visible_rows()
+                    .iter()
+                    .filter(|record| records.contains(record))
+                    .copied()
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Writes the `columns` (where `0` is the index column) of the records
+/// `rows` of `tab` in the given format. If any record of the tab is
+/// annotated, the annotations are added as an extra column.
+pub fn export(
+    tab: &Tab,
+    rows: &[usize],
+    columns: &[usize],
+    format: ExportFormat,
+    writer: impl Write,
+) -> anyhow::Result<()> {
+    let data = tab.data();
+    let mut header: Vec<String> = columns
+        .iter()
+        .map(|column| {
+            data.column_info(*column)
+                .map(|info| info.name().clone())
+                .unwrap_or_default()
+        })
+        .collect();
+    let with_annotations = !tab.annotations().is_empty();
+    if with_annotations {
+        header.push("annotation".into());
+    }
+
+    let records = rows.iter().filter_map(|record| {
+        let line = data.line(*record)?;
+        let mut values: Vec<String> = columns
+            .iter()
+            .map(|column| line.value(*column).unwrap_or_default().to_string())
+            .collect();
+        if with_annotations {
+            values.push(
+                tab.annotation(*record)
+                    .map(format_annotation)
+                    .unwrap_or_default(),
+            );
+        }
+        Some(values)
+    });
+
+    match format {
+        ExportFormat::Text => write_text(writer, &header, records),
+        ExportFormat::Csv => write_csv(writer, &header, records),
+        ExportFormat::Jsonl => write_jsonl(writer, &header, records),
+        ExportFormat::Markdown => write_markdown(writer, &header, records),
+        ExportFormat::Html => write_html(writer, &header, records),
+    }
+}
+
+/// Returns `true` if writing failed because the reader has closed the pipe
+pub fn is_broken_pipe(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        let io_error = match cause.downcast_ref::<csv::Error>().map(csv::Error::kind) {
+            Some(csv::ErrorKind::Io(io_error)) => Some(io_error),
+            _ => cause.downcast_ref::<std::io::Error>(),
+        };
+        io_error.is_some_and(|e| e.kind() == std::io::ErrorKind::BrokenPipe)
+    })
+}
+
+/// Sorts records by the values of a column (where `0` is the index column).
+/// Numbers and timestamps are compared by their value, everything else
+/// alphabetically.
+pub fn sort_rows(data: &dyn LogData, rows: &mut [usize], column: usize) {
+    let value = |record: &usize| {
+        data.line(*record)
+            .and_then(|line| line.value(column))
+            .unwrap_or_default()
+    };
+    rows.sort_by(|a, b| {
+        let (a, b) = (value(a), value(b));
+        match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+            (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+            _ => match (parse_timestamp(a), parse_timestamp(b)) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                _ => a.cmp(b),
+            },
+        }
+    });
+}
+
+/// Formats an annotation as single value, e.g. `#benign checked with IT`
+fn format_annotation(annotation: &Annotation) -> String {
+    annotation
+        .tags()
+        .iter()
+        .map(|tag| format!("#{tag}"))
+        .chain(std::iter::once(annotation.note().clone()).filter(|n| !n.is_empty()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn write_text(
+    mut writer: impl Write,
+    header: &[String],
+    records: impl Iterator<Item = Vec<String>>,
+) -> anyhow::Result<()> {
+    // all rows must be known to find the width of the columns
+    let records: Vec<_> = records.collect();
+    let mut widths: Vec<usize> = header.iter().map(|name| name.width()).collect();
+    for record in records.iter() {
+        for (width, value) in widths.iter_mut().zip(record.iter()) {
+            *width = usize::max(*width, value.width());
+        }
+    }
+
+    for record in std::iter::once(header).chain(records.iter().map(Vec::as_slice)) {
+        let mut line = String::new();
+        for (idx, (value, width)) in record.iter().zip(widths.iter()).enumerate() {
+            line.push_str(value);
+            if idx + 1 < record.len() {
+                line.push_str(&" ".repeat(width - value.width() + 2));
+            }
+        }
+        writeln!(writer, "{}", line.trim_end())?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_csv(
+    writer: impl Write,
+    header: &[String],
+    records: impl Iterator<Item = Vec<String>>,
+) -> anyhow::Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record(header)?;
+    for record in records {
+        writer.write_record(&record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_jsonl(
+    mut writer: impl Write,
+    header: &[String],
+    records: impl Iterator<Item = Vec<String>>,
+) -> anyhow::Result<()> {
+    // the object is written manually to keep the order of the columns
+    for record in records {
+        let fields = header
+            .iter()
+            .zip(record.iter())
+            .map(|(name, value)| {
+                Ok(format!(
+                    "{}:{}",
+                    serde_json::to_string(name)?,
+                    serde_json::to_string(value)?
+                ))
+            })
+            .collect::<serde_json::Result<Vec<_>>>()?;
+        writeln!(writer, "{{{}}}", fields.join(","))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_markdown(
+    mut writer: impl Write,
+    header: &[String],
+    records: impl Iterator<Item = Vec<String>>,
+) -> anyhow::Result<()> {
+    let escape = |value: &str| value.replace('|', "\\|").replace(['\r', '\n'], " ");
+    let row = |values: &[String]| {
+        let cells: Vec<_> = values.iter().map(|v| escape(v)).collect();
+        format!("| {} |", cells.join(" | "))
+    };
+
+    writeln!(writer, "{}", row(header))?;
+    writeln!(writer, "|{}", "---|".repeat(header.len()))?;
+    for record in records {
+        writeln!(writer, "{}", row(&record))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_html(
+    mut writer: impl Write,
+    header: &[String],
+    records: impl Iterator<Item = Vec<String>>,
+) -> anyhow::Result<()> {
+    let escape = |value: &str| {
+        value
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    };
+    let row = |values: &[String], tag: &str| {
+        let cells: String = values
+            .iter()
+            .map(|v| format!("<{tag}>{}</{tag}>", escape(v)))
+            .collect();
+        format!("    <tr>{cells}</tr>")
+    };
+
+    writeln!(writer, "<table>")?;
+    writeln!(writer, "  <thead>")?;
+    writeln!(writer, "{}", row(header, "th"))?;
+    writeln!(writer, "  </thead>")?;
+    writeln!(writer, "  <tbody>")?;
+    for record in records {
+        writeln!(writer, "{}", row(&record, "td"))?;
+    }
+    writeln!(writer, "  </tbody>")?;
+    writeln!(writer, "</table>")?;
+    writer.flush()?;
+    Ok(())
+}
@@ -1,45 +1,272 @@
-use crate::app::{App, AppResult};
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::{
+    app::{App, AppResult},
+    Action, Key, Lookup, RawView,
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 
 /// Handles the key events and updates the state of [`App`].
 pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    // messages and errors are only displayed until the next key is pressed
+    app.set_message(None);
+    app.set_error(None);
+
+    if app.command_line().is_some() {
+        return handle_command_line_key_events(key_event, app);
+    }
+    if app.overlay().is_some() {
+        return handle_overlay_key_events(key_event, app);
+    }
+    let key = Key::from(key_event);
+
+    // the key is the argument of the previous action, e.g. the name of a
+    // mark
+    if let Some(action) = *app.pending_action() {
+        let count = *app.count();
+        app.set_pending_action(None);
+        app.set_count(None);
+        if let Some(argument) = key.char() {
+            perform(action, count, Some(argument), app);
+        }
+        return Ok(());
+    }
+
+    // digits which are typed before an action are its count, unless a digit
+    // is bound to an action itself
+    if let Some(digit) = key.digit() {
+        let starts_count = digit > 0
+            && app
+                .keymap()
+                .lookup(&[key], |action| is_available(action, app))
+                == Lookup::None;
+        if app.pending_keys().is_empty() && (app.count().is_some() || starts_count) {
+            let count = app.count().unwrap_or_default();
+            app.set_count(Some(count.saturating_mul(10).saturating_add(digit)));
+            return Ok(());
+        }
+    }
+
+    let mut keys = app.pending_keys().clone();
+    keys.push(key);
+    app.set_pending_keys(Vec::new());
+    match app
+        .keymap()
+        .lookup(&keys, |action| is_available(action, app))
+    {
+        Lookup::None => {
+            app.set_count(None);
+        }
+        Lookup::Prefix => {
+            app.set_pending_keys(keys);
+        }
+        Lookup::Action(action) if action.takes_argument() => {
+            app.set_pending_action(Some(action));
+        }
+        Lookup::Action(action) => {
+            let count = *app.count();
+            app.set_count(None);
+            perform(action, count, key.char(), app);
+        }
+    }
+    Ok(())
+}
+
+/// Returns `false` if the action cannot be performed in the current state,
+/// so that its keys can be bound to another action
+fn is_available(action: Action, app: &App) -> bool {
+    match action {
+        Action::ClearSelection | Action::YankSelection => app.tab().selection().is_some(),
+        _ => true,
+    }
+}
+
+/// Performs an action. `count` is the number which has been typed before
+/// the keys of the action, and `key` is the last key if it is a character.
+fn perform(action: Action, count: Option<usize>, key: Option<char>, app: &mut App) {
+    let steps = count.unwrap_or(1);
+    let page_size = usize::from(*app.page_size());
+    match action {
+        Action::Quit => app.quit(),
+        Action::Help => app.show_help(),
+        Action::CommandLine => app.open_command_line(),
+        Action::Down => app.forward(steps),
+        Action::Up => app.backward(steps),
+        Action::PageDown => app.page_forward(page_size.saturating_sub(1).max(1), steps),
+        Action::PageUp => app.page_backward(page_size.saturating_sub(1).max(1), steps),
+        Action::HalfPageDown => app.page_forward((page_size / 2).max(1), steps),
+        Action::HalfPageUp => app.page_backward((page_size / 2).max(1), steps),
+        Action::FirstRow => match count {
+            Some(row) => app.goto(row.saturating_sub(1)),
+            None => app.begin(),
+        },
+        Action::LastRow => match count {
+            Some(row) => app.goto(row.saturating_sub(1)),
+            None => app.end(),
+        },
+        Action::NextBucket => (0..steps).for_each(|_| app.next_bucket()),
+        Action::PreviousBucket => (0..steps).for_each(|_| app.previous_bucket()),
+        Action::ToggleTimeline => app.toggle_timeline(),
+        Action::Grep => app.open_command_line_with("grep ".into()),
+        Action::PivotOnColumn => app.pivot_on_column(),
+        Action::PivotOnValue => app.pivot_on_value(),
+        Action::Back => app.back(),
+        Action::ScrollRight => app.right(steps * 8),
+        Action::ScrollLeft => app.left(steps * 8),
+        Action::NextColumn => (0..steps).for_each(|_| app.next_column()),
+        Action::PreviousColumn => (0..steps).for_each(|_| app.previous_column()),
+        Action::ColumnStats => app.compute_column_stats(app.current_column()),
+        Action::ToggleTimestamps => app.toggle_timestamp_display(),
+        Action::ToggleRawLine => app.toggle_raw_view(RawView::CurrentRow),
+        Action::ToggleRawView => app.toggle_raw_view(RawView::Screen),
+        Action::HexDump => app.show_hex_dump(),
+        Action::ToggleWrap => {
+            app.set_wrap(!app.wrap());
+        }
+        Action::ToggleMaskUnicode => {
+            app.set_mask_unicode(!app.mask_unicode());
+        }
+        Action::ShowSuspicious => app.show_suspicious_values(),
+        Action::NextTab => (0..steps).for_each(|_| app.next_tab()),
+        Action::PreviousTab => (0..steps).for_each(|_| app.previous_tab()),
+        Action::SelectTab => {
+            match count.or_else(|| key.and_then(|c| c.to_digit(10))?.try_into().ok()) {
+                Some(tab) => app.select_tab(tab.saturating_sub(1)),
+                None => app.next_tab(),
+            }
+        }
+        Action::SwitchPane => app.switch_pane(),
+        Action::ToggleSync => app.toggle_sync_by_time(),
+        Action::ToggleBookmark => app.toggle_bookmark(),
+        Action::ShowBookmarks => app.show_bookmarks(),
+        Action::SetMark => {
+            if let Some(name) = key.filter(char::is_ascii_alphabetic) {
+                app.set_mark(name);
+            }
+        }
+        Action::JumpToMark => {
+            if let Some(name) = key.filter(char::is_ascii_alphabetic) {
+                app.jump_to_mark(name);
+            }
+        }
+        Action::NextBookmark => (0..steps).for_each(|_| app.next_bookmark()),
+        Action::PreviousBookmark => (0..steps).for_each(|_| app.previous_bookmark()),
+        Action::ToggleSelection => app.tab_mut().toggle_selection(),
+        Action::ClearSelection => app.tab_mut().clear_selection(),
+        Action::EditNote => app.edit_note(),
+        Action::YankRow => app.yank_row(),
+        Action::YankCell => app.yank_cell(),
+        Action::YankSelection => app.yank_selection(),
+    }
+}
+
+/// Handles the key events while an overlay is displayed
+fn handle_overlay_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    let page_size = usize::from(*app.page_size() / 2);
+    let ctrl = key_event.modifiers == KeyModifiers::CONTROL;
+    if let Some(overlay) = app.overlay_mut().filter(|overlay| overlay.is_filterable()) {
+        match key_event.code {
+            // Clear the filter first, then close the overlay
+            KeyCode::Esc => {
+                if !overlay.clear_filter() {
+                    app.close_overlay();
+                }
+                return Ok(());
+            }
+            KeyCode::Backspace => {
+                overlay.pop_filter();
+                return Ok(());
+            }
+            KeyCode::Char(c) if !ctrl => {
+                overlay.push_filter(c);
+                return Ok(());
+            }
+            _ => {}
+        }
+    }
+
     match key_event.code {
-        // Exit application on `ESC` or `q`
         KeyCode::Esc | KeyCode::Char('q') => {
-            app.quit();
+            app.close_overlay();
         }
-        // Exit application on `Ctrl-C`
-        KeyCode::Char('c') | KeyCode::Char('C') => {
-            if key_event.modifiers == KeyModifiers::CONTROL {
-                app.quit();
-            }
+        KeyCode::Enter => {
+            app.submit_overlay();
+            app.close_overlay();
         }
         KeyCode::Down => {
-            app.forward(1);
+            if let Some(overlay) = app.overlay_mut() {
+                overlay.select_next(1);
+            }
         }
         KeyCode::Up => {
-            app.backward(1);
-        }
-        KeyCode::Right => {
-            app.right(8);
-        }
-        KeyCode::Left => {
-            app.left(8);
+            if let Some(overlay) = app.overlay_mut() {
+                overlay.select_previous(1);
+            }
         }
         KeyCode::PageDown => {
-            app.forward((app.page_size() / 2).into());
+            if let Some(overlay) = app.overlay_mut() {
+                overlay.select_next(page_size);
+            }
         }
         KeyCode::PageUp => {
-            app.backward((app.page_size() / 2).into());
-        }
-        KeyCode::Char('G') => {
-            app.end();
-        }
-        KeyCode::Char('g') => {
-            app.begin();
+            if let Some(overlay) = app.overlay_mut() {
+                overlay.select_previous(page_size);
+            }
         }
-        // Other handlers you could add here.
         _ => {}
     }
     Ok(())
 }
+
+/// Handles the key events while the command line is open
+fn handle_command_line_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    let ctrl = key_event.modifiers == KeyModifiers::CONTROL;
+    match key_event.code {
+        KeyCode::Esc => app.close_command_line(),
+        KeyCode::Enter => app.submit_command_line(),
+        KeyCode::Up => app.history_previous(),
+        KeyCode::Down => app.history_next(),
+        KeyCode::Tab => app.complete_command_line(),
+        _ => {
+            let Some(command_line) = app.command_line_mut() else {
+                return Ok(());
+            };
+            match key_event.code {
+                // Close the command line if there is nothing left to delete
+                KeyCode::Backspace => {
+                    if command_line.input().is_empty() {
+                        app.close_command_line();
+                    } else {
+                        command_line.pop();
+                    }
+                }
+                KeyCode::Delete => command_line.delete(),
+                KeyCode::Left => command_line.move_left(),
+                KeyCode::Right => command_line.move_right(),
+                KeyCode::Home => command_line.move_to_start(),
+                KeyCode::End => command_line.move_to_end(),
+                KeyCode::Char('a') if ctrl => command_line.move_to_start(),
+                KeyCode::Char('e') if ctrl => command_line.move_to_end(),
+                KeyCode::Char('u') if ctrl => command_line.delete_to_start(),
+                KeyCode::Char('w') if ctrl => command_line.delete_word(),
+                KeyCode::Char(c) if !ctrl => command_line.push(c),
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Handles pasted text, which is inserted into the command line if it is open
+pub fn handle_paste_event(text: String, app: &mut App) -> AppResult<()> {
+    if let Some(command_line) = app.command_line_mut() {
+        command_line.push_str(&text);
+    }
+    Ok(())
+}
+
+/// Handles the mouse events and updates the state of [`App`].
+pub fn handle_mouse_events(mouse_event: MouseEvent, app: &mut App) -> AppResult<()> {
+    if let MouseEventKind::Down(MouseButton::Left) = mouse_event.kind {
+        app.click(mouse_event.column, mouse_event.row);
+    }
+    Ok(())
+}
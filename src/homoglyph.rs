@@ -0,0 +1,112 @@
+use std::{fmt::Display, ops::Range};
+
+use getset::Getters;
+use unicode_security::{skeleton, MixedScript};
+
+/// A word which mixes scripts or contains characters that look like ASCII
+/// characters, such as `svchost.exe` with a Cyrillic `о`
+#[derive(Clone, Debug, Getters)]
+#[getset(get = "pub")]
+pub struct Homoglyph {
+    /// byte range of the word in the text
+    range: Range<usize>,
+
+    /// the word contains characters of scripts which are not used together
+    mixed_script: bool,
+
+    /// the word with confusable characters replaced by their prototypes,
+    /// as defined by Unicode Technical Standard #39
+    looks_like: String,
+}
+
+impl Display for Homoglyph {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "looks like: {}", self.looks_like)?;
+        if self.mixed_script {
+            write!(f, " (mixed scripts)")?;
+        }
+        Ok(())
+    }
+}
+
+/// characters which separate words, e.g. in paths and command lines
+fn is_separator(ch: char) -> bool {
+    ch.is_whitespace() || ch.is_control() || "/\\,;:=|\"'`()[]{}<>".contains(ch)
+}
+
+/// Finds the words of a text which mix scripts or which look like ASCII
+/// words without being ASCII
+pub fn find_homoglyphs(text: &str) -> Vec<Homoglyph> {
+    if text.is_ascii() {
+        return Vec::new();
+    }
+
+    let mut homoglyphs = Vec::new();
+    let mut start = 0;
+    for (end, separator) in text
+        .char_indices()
+        .filter(|(_, ch)| is_separator(*ch))
+        .map(|(idx, ch)| (idx, ch.len_utf8()))
+        .chain(std::iter::once((text.len(), 0)))
+    {
+        let word = &text[start..end];
+        if !word.is_ascii() {
+            let looks_like: String = word
+                .chars()
+                .flat_map(|ch| {
+                    if ch.is_ascii() {
+                        vec![ch]
+                    } else {
+                        skeleton(ch.encode_utf8(&mut [0; 4])).collect()
+                    }
+                })
+                .collect();
+            let mixed_script = !word.is_single_script();
+            let confusable = looks_like.is_ascii() && looks_like != word;
+            if mixed_script || confusable {
+                homoglyphs.push(Homoglyph {
+                    range: start..end,
+                    mixed_script,
+                    looks_like,
+                });
+            }
+        }
+        start = end + separator;
+    }
+    homoglyphs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_homoglyphs;
+
+    #[test]
+    fn test_confusable_word() {
+        let homoglyphs = find_homoglyphs("C:\\Windows\\svch\u{43e}st.exe -k netsvcs");
+        assert_eq!(homoglyphs.len(), 1);
+        assert_eq!(homoglyphs[0].range(), &(11..23));
+        assert_eq!(homoglyphs[0].looks_like(), "svchost.exe");
+        assert!(homoglyphs[0].mixed_script());
+    }
+
+    #[test]
+    fn test_single_script_words() {
+        assert!(find_homoglyphs("привет мир").is_empty());
+        assert!(find_homoglyphs("café").is_empty());
+        assert!(find_homoglyphs("plain ascii").is_empty());
+    }
+
+    #[test]
+    fn test_augmented_script() {
+        // Japanese mixes Han and Hiragana
+        assert!(find_homoglyphs("漢字ひらがな").is_empty());
+    }
+
+    #[test]
+    fn test_mixed_script() {
+        let homoglyphs = find_homoglyphs("user=helloλ");
+        assert_eq!(homoglyphs.len(), 1);
+        assert_eq!(homoglyphs[0].range(), &(5..12));
+        assert!(homoglyphs[0].mixed_script());
+    }
+}
@@ -1,8 +1,20 @@
 use std::{
-    fs::File, io::{stdin, Read, Stdin}
+    collections::HashMap,
+    fs::File,
+    io::{stdin, BufRead, BufReader, Cursor, Read, Seek, SeekFrom, Stdin},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::SystemTime,
 };
 
+use anyhow::Context;
 use clio::ClioPath;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use encoding_rs_io::DecodeReaderBytesBuilder;
+use getset::Getters;
 
 pub enum InputReader {
     Stdin(Stdin),
@@ -29,3 +41,359 @@ impl TryFrom<&ClioPath> for InputReader {
         }
     }
 }
+
+/// Position of a record in the original input, in bytes
+#[derive(Clone, Copy, Debug, Getters)]
+#[getset(get = "pub")]
+pub struct RawPosition {
+    offset: u64,
+    length: u64,
+}
+
+impl RawPosition {
+    pub fn new(offset: u64, length: u64) -> Self {
+        Self { offset, length }
+    }
+}
+
+/// number of bytes at the start of the input which are used to detect its
+/// encoding
+const SNIFF_LENGTH: usize = 64 * 1024;
+
+/// maximum number of records whose bytes are kept by [`RawInput`]; this is
+/// a lot more than fit on a screen, so that rendering does not read the file
+const RAW_CACHE_SIZE: usize = 1024;
+
+/// Where the original bytes of an input can be read again
+#[derive(Debug)]
+enum RawSource {
+    /// a file, with the size and modification time it had when it was read,
+    /// to detect if it has been changed since then
+    File {
+        path: PathBuf,
+        size: u64,
+        modified: Option<SystemTime>,
+    },
+
+    /// stdin cannot be read twice, and its contents are not kept, so that
+    /// the input is not held in memory twice
+    Stdin,
+}
+
+/// The original input of a decoded text
+#[derive(Debug, Getters)]
+pub struct RawInput {
+    #[getset(get = "pub")]
+    encoding: &'static Encoding,
+    bom_length: usize,
+    source: RawSource,
+
+    /// invalid bytes have been replaced while decoding the input, so the
+    /// positions in the decoded text do not match those in the input
+    lossy: Arc<AtomicBool>,
+
+    /// bytes of the records which have been read recently, by offset
+    cache: Mutex<HashMap<u64, Vec<u8>>>,
+}
+
+impl RawInput {
+    /// Reads the bytes at `position` from the input. This fails if the file
+    /// has been changed since it was read, because the bytes would not
+    /// belong to the record anymore.
+    pub fn read(&self, position: &RawPosition) -> anyhow::Result<Vec<u8>> {
+        self.check_lossless()?;
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(bytes) = cache.get(position.offset()) {
+            if u64::try_from(bytes.len()).ok() == Some(*position.length()) {
+                return Ok(bytes.clone());
+            }
+        }
+        let bytes = self.read_uncached(position)?;
+        if cache.len() >= RAW_CACHE_SIZE {
+            cache.clear();
+        }
+        cache.insert(*position.offset(), bytes.clone());
+        Ok(bytes)
+    }
+
+    fn read_uncached(&self, position: &RawPosition) -> anyhow::Result<Vec<u8>> {
+        let length = usize::try_from(*position.length())?;
+        match &self.source {
+            RawSource::File {
+                path,
+                size,
+                modified,
+            } => {
+                let mut bytes = vec![0; length];
+                let mut file = File::open(path)
+                    .with_context(|| format!("unable to open {}", path.display()))?;
+                let metadata = file.metadata()?;
+                if metadata.len() != *size || metadata.modified().ok() != *modified {
+                    anyhow::bail!("{} has been changed since it was read", path.display());
+                }
+                file.seek(SeekFrom::Start(*position.offset()))?;
+                file.read_exact(&mut bytes)
+                    .with_context(|| format!("unable to read from {}", path.display()))?;
+                Ok(bytes)
+            }
+            RawSource::Stdin => {
+                anyhow::bail!(
+                    "the original input is not available, because it has been read from stdin"
+                )
+            }
+        }
+    }
+
+    /// Returns `true` if invalid bytes have been replaced while decoding the
+    /// input. The positions of the records are unknown then.
+    pub fn is_lossy(&self) -> bool {
+        self.lossy.load(Ordering::Relaxed)
+    }
+
+    /// Fails if the positions of the records are unknown, because invalid
+    /// bytes have been replaced while decoding the input
+    pub fn check_lossless(&self) -> anyhow::Result<()> {
+        if self.is_lossy() {
+            anyhow::bail!(
+                "the input contains invalid characters, so the records cannot be found in it"
+            );
+        }
+        Ok(())
+    }
+
+    /// Decodes bytes which have been read with [`RawInput::read`]
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        self.encoding
+            .decode_without_bom_handling(bytes)
+            .0
+            .into_owned()
+    }
+
+    /// Returns a helper which converts positions in the decoded `text`
+    /// to positions in the input
+    pub fn positions<'t>(&self, text: &'t str) -> PositionMapper<'t> {
+        PositionMapper {
+            text,
+            encoding: self.encoding,
+            text_offset: 0,
+            input_offset: self.text_offset(),
+        }
+    }
+
+    /// offset in the input where the decoded text starts, after the byte
+    /// order mark
+    pub fn text_offset(&self) -> u64 {
+        u64::try_from(self.bom_length).unwrap_or_default()
+    }
+
+    /// number of bytes which a part of the decoded text has in the input
+    pub fn encoded_length(&self, text: &str) -> u64 {
+        encoded_length(self.encoding, text)
+    }
+}
+
+/// Converts byte ranges of a decoded text to positions in the input. This
+/// is cheap as long as the ranges are requested in ascending order.
+pub struct PositionMapper<'t> {
+    text: &'t str,
+    encoding: &'static Encoding,
+    text_offset: usize,
+    input_offset: u64,
+}
+
+impl<'t> PositionMapper<'t> {
+    pub fn position(&mut self, start: usize, end: usize) -> RawPosition {
+        let offset = self.offset(start);
+        RawPosition::new(offset, self.offset(end) - offset)
+    }
+
+    /// Returns the offset in the input of a byte offset in the text
+    pub fn offset(&mut self, text_offset: usize) -> u64 {
+        if text_offset < self.text_offset {
+            self.input_offset -= self.encoded_length(text_offset, self.text_offset);
+        } else {
+            self.input_offset += self.encoded_length(self.text_offset, text_offset);
+        }
+        self.text_offset = text_offset;
+        self.input_offset
+    }
+
+    /// number of bytes which a part of the text has in the input
+    fn encoded_length(&self, start: usize, end: usize) -> u64 {
+        encoded_length(self.encoding, self.text.get(start..end).unwrap_or_default())
+    }
+}
+
+/// number of bytes which `text` has in the input, if it has been decoded
+/// from `encoding`
+fn encoded_length(encoding: &'static Encoding, text: &str) -> u64 {
+    let length = if encoding == UTF_16LE || encoding == UTF_16BE {
+        text.encode_utf16().count() * 2
+    } else if encoding == UTF_8 {
+        text.len()
+    } else {
+        // all remaining encodings are single-byte encodings
+        text.chars().count()
+    };
+    u64::try_from(length).unwrap_or_default()
+}
+
+/// Opens the input and decodes it while it is read. The encoding is taken
+/// from the byte order mark, if there is one. Otherwise the input is expected
+/// to be UTF-8, falling back to Windows-1252 if its start is not valid UTF-8.
+/// Invalid bytes after the start are replaced, see [`RawInput::is_lossy`].
+pub fn decoding_reader(input: &ClioPath) -> anyhow::Result<(impl BufRead, RawInput)> {
+    let mut reader = InputReader::try_from(input)?;
+    let source = match &reader {
+        InputReader::File(file) => {
+            let metadata = file.metadata()?;
+            RawSource::File {
+                path: input.path().to_path_buf(),
+                size: metadata.len(),
+                modified: metadata.modified().ok(),
+            }
+        }
+        InputReader::Stdin(_) => RawSource::Stdin,
+    };
+
+    let mut prefix = Vec::with_capacity(SNIFF_LENGTH);
+    (&mut reader)
+        .take(u64::try_from(SNIFF_LENGTH).unwrap_or(u64::MAX))
+        .read_to_end(&mut prefix)?;
+    let (encoding, bom_length) = match Encoding::for_bom(&prefix) {
+        Some(found) => found,
+        // the prefix may end in the middle of a character
+        None => match std::str::from_utf8(&prefix) {
+            Err(why) if why.error_len().is_some() => (WINDOWS_1252, 0),
+            _ => (UTF_8, 0),
+        },
+    };
+    let mut prefix = Cursor::new(prefix);
+    prefix.set_position(u64::try_from(bom_length)?);
+    let lossy = Arc::new(AtomicBool::new(false));
+    let validator = Utf8Validator {
+        inner: prefix.chain(reader),
+        enabled: encoding == UTF_8,
+        incomplete: Vec::new(),
+        lossy: Arc::clone(&lossy),
+    };
+    let decoder = DecodeReaderBytesBuilder::new()
+        .encoding(Some(encoding))
+        .build(validator);
+    Ok((
+        BufReader::new(decoder),
+        RawInput {
+            encoding,
+            bom_length,
+            source,
+            lossy,
+            cache: Mutex::default(),
+        },
+    ))
+}
+
+/// Checks that the bytes which are read are valid UTF-8. Otherwise, the
+/// decoder replaces invalid bytes by U+FFFD, which has another length, so
+/// that the positions in the decoded text cannot be mapped to the input.
+/// Other encodings which are detected do not change the length this way.
+struct Utf8Validator<R> {
+    inner: R,
+    enabled: bool,
+
+    /// bytes at the end of the last read which are the start of a character
+    incomplete: Vec<u8>,
+    lossy: Arc<AtomicBool>,
+}
+
+impl<R: Read> Read for Utf8Validator<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let count = self.inner.read(buf)?;
+        if !self.enabled || self.lossy.load(Ordering::Relaxed) {
+            return Ok(count);
+        }
+        if count == 0 {
+            // the input ends in the middle of a character
+            if !self.incomplete.is_empty() {
+                self.lossy.store(true, Ordering::Relaxed);
+            }
+            return Ok(count);
+        }
+
+        let mut bytes = std::mem::take(&mut self.incomplete);
+        bytes.extend_from_slice(&buf[..count]);
+        match std::str::from_utf8(&bytes) {
+            Ok(_) => (),
+            Err(why) if why.error_len().is_none() => {
+                self.incomplete = bytes.split_off(why.valid_up_to());
+            }
+            Err(_) => self.lossy.store(true, Ordering::Relaxed),
+        }
+        Ok(count)
+    }
+}
+
+/// Reads the whole input and decodes it, see [`decoding_reader`]
+pub fn read_decoded(input: &ClioPath) -> anyhow::Result<(String, RawInput)> {
+    let (mut reader, raw_input) = decoding_reader(input)?;
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    Ok((text, raw_input))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use clio::ClioPath;
+
+    use super::{read_decoded, SNIFF_LENGTH};
+
+    /// Writes `bytes` to a temporary file and decodes it
+    fn decode(bytes: &[u8]) -> (String, super::RawInput, tempfile::NamedTempFile) {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(bytes).unwrap();
+        let path = ClioPath::new(file.path()).unwrap();
+        let (text, raw_input) = read_decoded(&path).unwrap();
+        (text, raw_input, file)
+    }
+
+    #[test]
+    fn test_positions() {
+        let (text, raw_input, _file) = decode("\u{feff}ä,b\nc,d\n".as_bytes());
+        assert_eq!(text, "ä,b\nc,d\n");
+        assert!(!raw_input.is_lossy());
+
+        let start = text.find('c').unwrap();
+        let position = raw_input.positions(&text).position(start, start + 3);
+        assert_eq!(*position.offset(), 3 + 5);
+        assert_eq!(raw_input.read(&position).unwrap(), b"c,d");
+    }
+
+    #[test]
+    fn test_invalid_byte_after_sniffing() {
+        let mut bytes = "a,b\n".repeat(SNIFF_LENGTH / 4 + 1).into_bytes();
+        bytes.extend_from_slice(b"\xff,x\nc,d\n");
+        let (text, raw_input, _file) = decode(&bytes);
+        assert!(text.contains("\u{fffd},x\n"));
+        assert!(raw_input.is_lossy());
+
+        let start = text.find('c').unwrap();
+        let position = raw_input.positions(&text).position(start, start + 3);
+        assert!(raw_input.read(&position).is_err());
+    }
+
+    #[test]
+    fn test_character_across_reads() {
+        // a multi-byte character at the end of the sniffed prefix is valid
+        let mut bytes = "a".repeat(SNIFF_LENGTH - 1).into_bytes();
+        bytes.extend_from_slice("ä\n".as_bytes());
+        let (_, raw_input, _file) = decode(&bytes);
+        assert!(!raw_input.is_lossy());
+
+        // but not at the end of the input
+        let mut bytes = "a".repeat(SNIFF_LENGTH).into_bytes();
+        bytes.extend_from_slice(&"ä".as_bytes()[..1]);
+        let (_, raw_input, _file) = decode(&bytes);
+        assert!(raw_input.is_lossy());
+    }
+}
@@ -0,0 +1,581 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use clap::ValueEnum;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use getset::Getters;
+use serde::Deserialize;
+
+use crate::Action;
+
+/// A key together with the modifiers which have been pressed with it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        // the shift key is already part of upper case characters
+        let modifiers = match code {
+            KeyCode::Char(_) | KeyCode::BackTab => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        Self { code, modifiers }
+    }
+
+    /// Returns the digit if this key is a digit which has been pressed
+    /// without modifiers
+    pub fn digit(&self) -> Option<usize> {
+        match self.code {
+            KeyCode::Char(c) if self.modifiers.is_empty() => {
+                c.to_digit(10).and_then(|d| usize::try_from(d).ok())
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the character if this key is a character which has been
+    /// pressed without modifiers
+    pub fn char(&self) -> Option<char> {
+        match self.code {
+            KeyCode::Char(c) if self.modifiers.is_empty() => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Parses a sequence of keys in the notation of vim, e.g. `gg`, `]b`,
+    /// `<C-d>` or `<S-Right>`
+    pub fn parse_sequence(keys: &str) -> anyhow::Result<Vec<Key>> {
+        let mut sequence = Vec::new();
+        let mut rest = keys;
+        while let Some(c) = rest.chars().next() {
+            let named = rest
+                .strip_prefix('<')
+                .and_then(|name| name.find('>').map(|end| &name[..end]))
+                .filter(|name| !name.is_empty());
+            match named {
+                Some(name) => {
+                    sequence.push(Self::parse_named(name)?);
+                    rest = &rest[name.len() + 2..];
+                }
+                None => {
+                    sequence.push(Self::new(KeyCode::Char(c), KeyModifiers::NONE));
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+        if sequence.is_empty() {
+            anyhow::bail!("empty key sequence");
+        }
+        Ok(sequence)
+    }
+
+    fn parse_named(name: &str) -> anyhow::Result<Key> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = name;
+        while rest.len() > 2 && rest.as_bytes()[1] == b'-' {
+            modifiers |= match rest.as_bytes()[0].to_ascii_uppercase() {
+                b'C' => KeyModifiers::CONTROL,
+                b'A' | b'M' => KeyModifiers::ALT,
+                b'S' => KeyModifiers::SHIFT,
+                _ => anyhow::bail!("invalid modifier in <{name}>"),
+            };
+            rest = &rest[2..];
+        }
+
+        let code = match &rest.to_lowercase()[..] {
+            "esc" => KeyCode::Esc,
+            "enter" | "cr" | "return" => KeyCode::Enter,
+            "space" => KeyCode::Char(' '),
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "bs" | "backspace" => KeyCode::Backspace,
+            "del" | "delete" => KeyCode::Delete,
+            "ins" | "insert" => KeyCode::Insert,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pgup" | "pageup" => KeyCode::PageUp,
+            "pgdn" | "pagedown" => KeyCode::PageDown,
+            "lt" => KeyCode::Char('<'),
+            "gt" => KeyCode::Char('>'),
+            key => match key.strip_prefix('f').map(u8::from_str) {
+                Some(Ok(n)) if (1..=12).contains(&n) => KeyCode::F(n),
+                _ => {
+                    let mut chars = rest.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => KeyCode::Char(c),
+                        _ => anyhow::bail!("unknown key: <{name}>"),
+                    }
+                }
+            },
+        };
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        Self::new(event.code, event.modifiers)
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self.code {
+            KeyCode::Char(' ') => "Space".into(),
+            KeyCode::Char('<') => "lt".into(),
+            KeyCode::Char(c) if self.modifiers.is_empty() => return write!(f, "{c}"),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Esc => "Esc".into(),
+            KeyCode::Enter => "Enter".into(),
+            KeyCode::Tab => "Tab".into(),
+            KeyCode::BackTab => "BackTab".into(),
+            KeyCode::Backspace => "BS".into(),
+            KeyCode::Delete => "Del".into(),
+            KeyCode::Insert => "Ins".into(),
+            KeyCode::Up => "Up".into(),
+            KeyCode::Down => "Down".into(),
+            KeyCode::Left => "Left".into(),
+            KeyCode::Right => "Right".into(),
+            KeyCode::Home => "Home".into(),
+            KeyCode::End => "End".into(),
+            KeyCode::PageUp => "PgUp".into(),
+            KeyCode::PageDown => "PgDn".into(),
+            KeyCode::F(n) => format!("F{n}"),
+            code => format!("{code:?}"),
+        };
+        let mut modifiers = String::new();
+        for (modifier, prefix) in [
+            (KeyModifiers::CONTROL, "C-"),
+            (KeyModifiers::ALT, "A-"),
+            (KeyModifiers::SHIFT, "S-"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                modifiers.push_str(prefix);
+            }
+        }
+        write!(f, "<{modifiers}{name}>")
+    }
+}
+
+/// Formats a key sequence in the notation which [`Key::parse_sequence`]
+/// accepts
+pub fn format_sequence(keys: &[Key]) -> String {
+    keys.iter().map(Key::to_string).collect()
+}
+
+/// Sets of key bindings which loghawk ships with
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeymapPreset {
+    /// arrow keys and single letters; count prefixes cannot be typed,
+    /// because the digits `1` to `9` select tabs
+    #[default]
+    Default,
+
+    /// `hjkl`, `gg`, `G`, `Ctrl-D`, `Ctrl-U` and count prefixes
+    Vim,
+
+    /// `j`, `k`, `Space`, `b`, `d`, `u`, `g`, `G` and count prefixes
+    Less,
+}
+
+#[derive(Clone, Debug, Getters)]
+#[getset(get = "pub")]
+pub struct Binding {
+    keys: Vec<Key>,
+    action: Action,
+}
+
+/// Result of looking up the keys which have been pressed so far
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lookup {
+    /// no binding starts with these keys
+    None,
+
+    /// there are bindings which start with these keys, so more keys are
+    /// needed
+    Prefix,
+
+    Action(Action),
+}
+
+/// Maps key sequences to actions
+#[derive(Clone, Debug, Getters)]
+#[getset(get = "pub")]
+pub struct Keymap {
+    bindings: Vec<Binding>,
+}
+
+/// bindings which are part of every preset
+const COMMON_BINDINGS: &[(&str, Action)] = &[
+    ("<Esc>", Action::ClearSelection),
+    ("<C-c>", Action::Quit),
+    ("<F1>", Action::Help),
+    ("?", Action::Help),
+    (":", Action::CommandLine),
+    ("<Down>", Action::Down),
+    ("<Up>", Action::Up),
+    ("<Home>", Action::FirstRow),
+    ("<End>", Action::LastRow),
+    ("<S-Right>", Action::NextColumn),
+    ("<S-Left>", Action::PreviousColumn),
+    ("<Right>", Action::ScrollRight),
+    ("<Left>", Action::ScrollLeft),
+    ("<Enter>", Action::PivotOnColumn),
+    ("*", Action::PivotOnValue),
+    ("<BS>", Action::Back),
+    ("/", Action::Grep),
+    ("<C-PgDn>", Action::NextTab),
+    ("<C-PgUp>", Action::PreviousTab),
+    ("<Tab>", Action::SwitchPane),
+    ("t", Action::ToggleTimestamps),
+    ("W", Action::ToggleWrap),
+    ("U", Action::ToggleMaskUnicode),
+    ("!", Action::ShowSuspicious),
+    ("r", Action::ToggleRawLine),
+    ("R", Action::ToggleRawView),
+    ("x", Action::HexDump),
+    ("H", Action::ToggleTimeline),
+    ("s", Action::ColumnStats),
+    ("S", Action::ToggleSync),
+    ("B", Action::ShowBookmarks),
+    ("m", Action::SetMark),
+    ("'", Action::JumpToMark),
+    ("]b", Action::NextBookmark),
+    ("[b", Action::PreviousBookmark),
+    ("v", Action::ToggleSelection),
+    ("a", Action::EditNote),
+    ("y", Action::YankSelection),
+    ("yy", Action::YankRow),
+    ("yc", Action::YankCell),
+];
+
+const DEFAULT_BINDINGS: &[(&str, Action)] = &[
+    ("<Esc>", Action::Quit),
+    ("q", Action::Quit),
+    ("<PgDn>", Action::HalfPageDown),
+    ("<PgUp>", Action::HalfPageUp),
+    ("g", Action::FirstRow),
+    ("G", Action::LastRow),
+    ("1", Action::SelectTab),
+    ("2", Action::SelectTab),
+    ("3", Action::SelectTab),
+    ("4", Action::SelectTab),
+    ("5", Action::SelectTab),
+    ("6", Action::SelectTab),
+    ("7", Action::SelectTab),
+    ("8", Action::SelectTab),
+    ("9", Action::SelectTab),
+    (">", Action::NextBucket),
+    ("<lt>", Action::PreviousBucket),
+    ("b", Action::ToggleBookmark),
+];
+
+const VIM_BINDINGS: &[(&str, Action)] = &[
+    ("q", Action::Quit),
+    ("ZZ", Action::Quit),
+    ("j", Action::Down),
+    ("k", Action::Up),
+    ("<C-e>", Action::Down),
+    ("<C-y>", Action::Up),
+    ("h", Action::ScrollLeft),
+    ("l", Action::ScrollRight),
+    ("w", Action::NextColumn),
+    ("b", Action::PreviousColumn),
+    ("<C-f>", Action::PageDown),
+    ("<C-b>", Action::PageUp),
+    ("<PgDn>", Action::PageDown),
+    ("<PgUp>", Action::PageUp),
+    ("<C-d>", Action::HalfPageDown),
+    ("<C-u>", Action::HalfPageUp),
+    ("gg", Action::FirstRow),
+    ("G", Action::LastRow),
+    ("gt", Action::SelectTab),
+    ("gT", Action::PreviousTab),
+    ("<C-w>w", Action::SwitchPane),
+    ("<C-w><C-w>", Action::SwitchPane),
+    ("]t", Action::NextBucket),
+    ("[t", Action::PreviousBucket),
+    ("M", Action::ToggleBookmark),
+];
+
+const LESS_BINDINGS: &[(&str, Action)] = &[
+    ("q", Action::Quit),
+    ("Q", Action::Quit),
+    ("j", Action::Down),
+    ("e", Action::Down),
+    ("<C-n>", Action::Down),
+    ("<C-e>", Action::Down),
+    ("k", Action::Up),
+    ("<C-p>", Action::Up),
+    ("<C-k>", Action::Up),
+    ("<Space>", Action::PageDown),
+    ("f", Action::PageDown),
+    ("<C-f>", Action::PageDown),
+    ("<C-v>", Action::PageDown),
+    ("<PgDn>", Action::PageDown),
+    ("b", Action::PageUp),
+    ("<C-b>", Action::PageUp),
+    ("<PgUp>", Action::PageUp),
+    ("d", Action::HalfPageDown),
+    ("<C-d>", Action::HalfPageDown),
+    ("u", Action::HalfPageUp),
+    ("<C-u>", Action::HalfPageUp),
+    ("g", Action::FirstRow),
+    ("<lt>", Action::FirstRow),
+    ("G", Action::LastRow),
+    (">", Action::LastRow),
+    ("h", Action::Help),
+    (")", Action::NextBucket),
+    ("(", Action::PreviousBucket),
+    ("M", Action::ToggleBookmark),
+];
+
+impl Keymap {
+    pub fn preset(preset: KeymapPreset) -> Self {
+        let bindings = match preset {
+            KeymapPreset::Default => DEFAULT_BINDINGS,
+            KeymapPreset::Vim => VIM_BINDINGS,
+            KeymapPreset::Less => LESS_BINDINGS,
+        };
+        let bindings = COMMON_BINDINGS
+            .iter()
+            .chain(bindings.iter())
+            .map(|(keys, action)| Binding {
+                keys: Key::parse_sequence(keys).expect("invalid key in preset"),
+                action: *action,
+            })
+            .collect();
+        Self { bindings }
+    }
+
+    /// Binds a key sequence to an action. Bindings of other actions to the
+    /// same keys are removed.
+    pub fn bind(&mut self, keys: Vec<Key>, action: Action) {
+        self.bindings.retain(|binding| binding.keys != keys);
+        self.bindings.push(Binding { keys, action });
+    }
+
+    /// Returns the action which is bound to the keys. Only actions for which
+    /// `available` returns `true` are considered. If the keys are bound to
+    /// multiple actions, the one which has been bound first wins.
+    pub fn lookup(&self, keys: &[Key], available: impl Fn(Action) -> bool) -> Lookup {
+        let mut is_prefix = false;
+        for binding in self
+            .bindings
+            .iter()
+            .filter(|binding| binding.keys.starts_with(keys) && available(binding.action))
+        {
+            if binding.keys.len() == keys.len() {
+                return Lookup::Action(binding.action);
+            }
+            is_prefix = true;
+        }
+        if is_prefix {
+            Lookup::Prefix
+        } else {
+            Lookup::None
+        }
+    }
+
+    /// All key sequences which are bound to an action, e.g. `["gg", "<Home>"]`
+    pub fn keys_of(&self, action: Action) -> Vec<String> {
+        self.bindings
+            .iter()
+            .filter(|binding| binding.action == action)
+            .map(|binding| format_sequence(&binding.keys))
+            .collect()
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::preset(KeymapPreset::Default)
+    }
+}
+
+/// The `[keys]` section of the configuration file, e.g.
+///
+/// ```toml
+/// [keys]
+/// preset = "vim"
+///
+/// [keys.bindings]
+/// "<C-n>" = "next-tab"
+/// "<C-p>" = "previous-tab"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeymapConfig {
+    preset: KeymapPreset,
+    bindings: BTreeMap<String, Action>,
+}
+
+impl KeymapConfig {
+    /// Creates the keymap of the configured preset, with the configured
+    /// bindings added. `preset` overrides the configured preset.
+    pub fn keymap(&self, preset: Option<KeymapPreset>) -> anyhow::Result<Keymap> {
+        let mut keymap = Keymap::preset(preset.unwrap_or(self.preset));
+        for (keys, action) in self.bindings.iter() {
+            let keys = Key::parse_sequence(keys)
+                .map_err(|why| anyhow::anyhow!("invalid key binding '{keys}': {why}"))?;
+            keymap.bind(keys, *action);
+        }
+        Ok(keymap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    use super::{format_sequence, Key, Keymap, KeymapPreset, Lookup};
+    use crate::Action;
+
+    fn keys(sequence: &str) -> Vec<Key> {
+        Key::parse_sequence(sequence).unwrap()
+    }
+
+    #[test]
+    fn test_parse_sequence() {
+        let char = |c| Key::new(KeyCode::Char(c), KeyModifiers::NONE);
+        let cases = [
+            ("gg", vec![char('g'), char('g')]),
+            ("]b", vec![char(']'), char('b')]),
+            ("ü", vec![char('ü')]),
+            (
+                "<C-d>",
+                vec![Key::new(KeyCode::Char('d'), KeyModifiers::CONTROL)],
+            ),
+            (
+                "<S-Right>",
+                vec![Key::new(KeyCode::Right, KeyModifiers::SHIFT)],
+            ),
+            (
+                "<c-a-pgdn>",
+                vec![Key::new(
+                    KeyCode::PageDown,
+                    KeyModifiers::CONTROL | KeyModifiers::ALT,
+                )],
+            ),
+            (
+                "<C-w>w",
+                vec![
+                    Key::new(KeyCode::Char('w'), KeyModifiers::CONTROL),
+                    char('w'),
+                ],
+            ),
+            ("<F12>", vec![Key::new(KeyCode::F(12), KeyModifiers::NONE)]),
+            ("<Space>", vec![char(' ')]),
+            ("<lt>", vec![char('<')]),
+            // the shift key is part of upper case characters
+            ("<S-a>", vec![char('a')]),
+            // a `<` which does not start a name is a key itself
+            ("<", vec![char('<')]),
+            ("<>", vec![char('<'), char('>')]),
+        ];
+        for (sequence, expected) in cases {
+            assert_eq!(keys(sequence), expected, "{sequence}");
+        }
+
+        for invalid in ["", "<F13>", "<X-a>", "<Foo>"] {
+            assert!(Key::parse_sequence(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_format_sequence() {
+        for sequence in [
+            "gg",
+            "<C-d>",
+            "<S-Right>",
+            "<Space>",
+            "<lt>",
+            "<C-w>w",
+            "<F1>",
+        ] {
+            assert_eq!(format_sequence(&keys(sequence)), sequence);
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        let keymap = Keymap::preset(KeymapPreset::Default);
+        let all = |_| true;
+        assert_eq!(keymap.lookup(&keys("q"), all), Lookup::Action(Action::Quit));
+        assert_eq!(keymap.lookup(&keys("z"), all), Lookup::None);
+
+        // `y` is an action itself, and the start of `yy` and `yc`
+        assert_eq!(
+            keymap.lookup(&keys("y"), all),
+            Lookup::Action(Action::YankSelection)
+        );
+        let no_selection = |action| action != Action::YankSelection;
+        assert_eq!(keymap.lookup(&keys("y"), no_selection), Lookup::Prefix);
+        assert_eq!(
+            keymap.lookup(&keys("yy"), no_selection),
+            Lookup::Action(Action::YankRow)
+        );
+        assert_eq!(keymap.lookup(&keys("yq"), no_selection), Lookup::None);
+
+        // if the first binding is not available, the next one is used
+        assert_eq!(
+            keymap.lookup(&keys("<Esc>"), all),
+            Lookup::Action(Action::ClearSelection)
+        );
+        assert_eq!(
+            keymap.lookup(&keys("<Esc>"), |action| action != Action::ClearSelection),
+            Lookup::Action(Action::Quit)
+        );
+
+        let keymap = Keymap::preset(KeymapPreset::Vim);
+        assert_eq!(keymap.lookup(&keys("g"), all), Lookup::Prefix);
+        assert_eq!(
+            keymap.lookup(&keys("gg"), all),
+            Lookup::Action(Action::FirstRow)
+        );
+        assert_eq!(
+            keymap.lookup(&keys("<C-w><C-w>"), all),
+            Lookup::Action(Action::SwitchPane)
+        );
+    }
+
+    #[test]
+    fn test_bind() {
+        let mut keymap = Keymap::preset(KeymapPreset::Default);
+        keymap.bind(keys("q"), Action::Help);
+        assert_eq!(
+            keymap.lookup(&keys("q"), |_| true),
+            Lookup::Action(Action::Help)
+        );
+        assert_eq!(keymap.keys_of(Action::Quit), ["<C-c>", "<Esc>"]);
+    }
+
+    #[test]
+    fn test_presets() {
+        for preset in [KeymapPreset::Default, KeymapPreset::Vim, KeymapPreset::Less] {
+            let keymap = Keymap::preset(preset);
+            assert_eq!(
+                keymap.lookup(&keys("<F1>"), |_| true),
+                Lookup::Action(Action::Help),
+                "{preset:?}"
+            );
+
+            // digits can only be count prefixes if they are not bound
+            let digit = keymap.lookup(&keys("5"), |_| true);
+            if preset == KeymapPreset::Default {
+                assert_eq!(digit, Lookup::Action(Action::SelectTab));
+            } else {
+                assert_eq!(digit, Lookup::None, "{preset:?}");
+            }
+        }
+    }
+}
@@ -13,6 +13,9 @@ pub mod tui;
 /// Event handler.
 pub mod handler;
 
+/// Commands of the command line.
+pub mod commands;
+
 pub mod cli;
 mod csv_data;
 mod txt_data;
@@ -23,6 +26,28 @@ mod log_data;
 mod viewport;
 mod as_masked;
 mod log_line;
+mod tab;
+mod command_line;
+mod split_layout;
+mod timestamp;
+mod row_filter;
+mod status_bar;
+mod timeline;
+mod overlay;
+mod anomaly;
+mod column_stats;
+mod bookmarks;
+mod annotations;
+mod session;
+mod export;
+mod clipboard;
+mod action;
+mod keymap;
+mod config;
+mod raw_view;
+mod wrap;
+mod suspicious;
+mod homoglyph;
 
 pub use csv_data::*;
 pub use txt_data::*;
@@ -31,5 +56,28 @@ pub use log_data::*;
 pub use log_view_state::*;
 pub use viewport::*;
 pub use as_masked::*;
+pub use tab::*;
+pub use command_line::*;
+pub use split_layout::*;
+pub use log_line::*;
+pub use timestamp::*;
+pub use row_filter::*;
+pub use status_bar::*;
+pub use timeline::*;
+pub use overlay::*;
+pub use anomaly::*;
+pub use column_stats::*;
+pub use bookmarks::*;
+pub use annotations::*;
+pub use session::*;
+pub use export::*;
+pub use clipboard::*;
+pub use action::*;
+pub use keymap::*;
+pub use config::*;
+pub use raw_view::*;
+pub use wrap::*;
+pub use suspicious::*;
+pub use homoglyph::*;
 
-pub mod tui_helper;
\ No newline at end of file
+pub mod tui_helper;
@@ -1,8 +1,9 @@
-use getset::Getters;
+use encoding_rs::Encoding;
+use getset::{Getters, Setters};
 use ratatui::widgets::{ListItem, Row};
-use std::fmt::Debug;
+use std::fmt::{Debug, Display};
 
-use crate::ViewPort;
+use crate::{LogLine, RawInput, TimestampDisplay, ViewPort};
 
 #[derive(Clone, Copy, Debug)]
 pub enum ColumnWidth {
@@ -43,12 +44,49 @@ impl TryFrom<ColumnWidth> for u16 {
 #[derive(Getters, Debug)]
 #[getset(get = "pub")]
 pub struct ColumnInfo {
+    name: String,
     width: ColumnWidth,
 }
 
 impl ColumnInfo {
-    pub fn new(width: ColumnWidth) -> Self {
-        Self { width }
+    pub fn new(name: String, width: ColumnWidth) -> Self {
+        Self { name, width }
+    }
+}
+
+/// A part of the input file which could not be parsed as expected
+#[derive(Clone, Debug, Getters, Setters)]
+#[getset(get = "pub")]
+pub struct ParseProblem {
+    /// index of the record which contains the problematic input
+    record: usize,
+
+    /// line number in the input file, starting with `1`
+    line: u64,
+
+    /// byte offset in the input file, if it is known
+    #[getset(set = "pub")]
+    byte: Option<u64>,
+    message: String,
+}
+
+impl ParseProblem {
+    pub fn new(record: usize, line: u64, byte: Option<u64>, message: String) -> Self {
+        Self {
+            record,
+            line,
+            byte,
+            message,
+        }
+    }
+}
+
+impl Display for ParseProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.byte {
+            Some(byte) => write!(f, "line {} (byte {byte}): {}", self.line, self.message),
+            None => write!(f, "line {}: {}", self.line, self.message),
+        }
     }
 }
 
@@ -127,21 +165,129 @@ wrap_iterator!(IndexRows, ListItem<'_>);
 wrap_iterator!(DataRows, Row<'_>);
 wrap_iterator!(DataWidths, usize);
 
-pub trait LogData: Debug {
+pub trait LogData: Debug + Send + Sync {
+    /// name of the file format, e.g. `CSV`
+    fn format_name(&self) -> &'static str;
+
+    /// the input from which the data has been read
+    fn raw_input(&self) -> &RawInput;
+
+    /// encoding in which the data has been read
+    fn encoding(&self) -> &'static Encoding {
+        self.raw_input().encoding()
+    }
+
     fn len(&self) -> usize;
+
+    /// Parts of the input which could not be parsed properly
+    fn parse_problems(&self) -> &[ParseProblem] {
+        &[]
+    }
     fn is_empty(&self) -> bool;
+    fn line(&self, idx: usize) -> Option<&LogLine>;
 
     fn index_info(&self) -> &ColumnInfo;
     fn data_columns(&self) -> usize;
     fn data_infos(&self, idx: usize) -> Option<&ColumnInfo>;
     fn iter_data_columns(&self) -> IterDataColumns<'_>;
-    fn index_rows(&self, viewport: &ViewPort, mask_unicode: bool) -> IndexRows<'_>;
-    fn data_rows(&self, viewport: &ViewPort, mask_unicode: bool) -> DataRows<'_>;
+    fn index_rows<'d>(
+        &'d self,
+        rows: &'d [usize],
+        mask_unicode: bool,
+        timestamp_display: TimestampDisplay,
+    ) -> IndexRows<'d>;
+    fn data_rows<'d>(
+        &'d self,
+        rows: &'d [usize],
+        viewport: &ViewPort,
+        mask_unicode: bool,
+    ) -> DataRows<'d>;
+
+    /// Reads the original bytes of a record from the input
+    fn raw_bytes(&self, idx: usize) -> anyhow::Result<Vec<u8>> {
+        self.raw_input().check_lossless()?;
+        let position = self
+            .line(idx)
+            .and_then(|line| line.position())
+            .ok_or_else(|| anyhow::anyhow!("the position of this record is unknown"))?;
+        self.raw_input().read(position)
+    }
+
+    /// Reads a record from the input as it is, without parsing it
+    fn raw_line(&self, idx: usize) -> anyhow::Result<String> {
+        Ok(self.raw_input().decode(&self.raw_bytes(idx)?))
+    }
+
+    /// Returns a line which could not be split into columns, and which is
+    /// displayed across the data area instead
+    fn unparsed_line(&self, idx: usize) -> Option<&str> {
+        self.line(idx)
+            .filter(|line| line.is_unparsed())
+            .and_then(|line| line.contents(0))
+    }
+
+    /// Information about a column, where column `0` is the index column
+    fn column_info(&self, column: usize) -> Option<&ColumnInfo> {
+        if column == 0 {
+            Some(self.index_info())
+        } else {
+            self.data_infos(column - 1)
+        }
+    }
+
+    /// Number of columns, including the index column
+    fn column_count(&self) -> usize {
+        self.data_columns() + 1
+    }
+
+    /// Finds a column by its name (ignoring case) or by its number,
+    /// starting with `1` for the index column
+    fn find_column(&self, name: &str) -> Option<usize> {
+        (0..self.column_count())
+            .find(|c| {
+                self.column_info(*c)
+                    .map(|info| info.name().eq_ignore_ascii_case(name))
+                    .unwrap_or(false)
+            })
+            .or_else(|| {
+                name.parse::<usize>()
+                    .ok()
+                    .filter(|n| (1..=self.column_count()).contains(n))
+                    .map(|n| n - 1)
+            })
+    }
+
+    /// Returns the data column which is displayed at the left edge of the
+    /// data area if the view is scrolled horizontally by `hoffset`. Column
+    /// numbering is the same as in [`LogData::column_info`].
+    fn column_at(&self, hoffset: usize) -> usize {
+        let mut skip = hoffset;
+        for (idx, width) in self
+            .iter_data_columns()
+            .map(|c| usize::try_from(*c.width()).unwrap_or_default())
+            .enumerate()
+        {
+            if skip < width {
+                return idx + 1;
+            }
+            skip -= width;
+        }
+        self.data_columns()
+    }
+
+    /// Returns the horizontal scroll offset at which a column starts. Column
+    /// numbering is the same as in [`LogData::column_info`].
+    fn column_offset(&self, column: usize) -> usize {
+        self.iter_data_columns()
+            .take(column.saturating_sub(1))
+            .map(|c| usize::try_from(*c.width()).unwrap_or_default())
+            .sum()
+    }
 
     fn data_widths<'d>(&'d self, _viewport: &ViewPort) -> DataWidths<'d> {
         DataWidths::from(
             self.iter_data_columns()
-                .map(|c| usize::try_from(*c.width()).unwrap()),
+                .map(|c| usize::try_from(*c.width()).unwrap_or_default()),
         )
     }
 }
@@ -1,31 +1,109 @@
 use anyhow::bail;
+use chrono::{DateTime, Utc};
 use csv::StringRecord;
 
+use crate::{find_homoglyphs, Homoglyph, RawPosition};
+
 #[derive(Debug)]
 pub struct LogLine {
     key: String,
+    timestamp: Option<DateTime<Utc>>,
     contents: Vec<String>,
+
+    /// where the record has been found in the input
+    position: Option<RawPosition>,
+
+    /// words which mix scripts or look like other words, by column; they
+    /// are searched once, because they are displayed whenever the record is
+    /// rendered
+    homoglyphs: Vec<(usize, Vec<Homoglyph>)>,
+
+    /// the line could not be split into columns, and is kept as it is
+    unparsed: bool,
 }
 
 impl LogLine {
-    pub fn new(key: String, contents: String) -> Result<Self, anyhow::Error> {
-        Ok(Self {
+    pub fn new(key: String, contents: String) -> Self {
+        let contents = vec![contents];
+        Self {
+            homoglyphs: homoglyphs_by_column(&key, &contents),
             key,
-            contents: vec![contents],
-        })
+            timestamp: None,
+            contents,
+            position: None,
+            unparsed: false,
+        }
+    }
+
+    /// Creates a record without key for a line which could not be split
+    /// into columns
+    pub fn unparsed(line: String) -> Self {
+        Self {
+            unparsed: true,
+            ..Self::new(String::new(), line)
+        }
+    }
+
+    pub fn is_unparsed(&self) -> bool {
+        self.unparsed
     }
 
     pub fn key_value(&self) -> &str {
         &self.key
     }
 
+    /// the index value, parsed as timestamp
+    pub fn timestamp(&self) -> Option<&DateTime<Utc>> {
+        self.timestamp.as_ref()
+    }
+
+    pub fn set_timestamp(&mut self, timestamp: Option<DateTime<Utc>>) {
+        self.timestamp = timestamp;
+    }
+
+    pub fn position(&self) -> Option<&RawPosition> {
+        self.position.as_ref()
+    }
+
+    pub fn set_position(&mut self, position: Option<RawPosition>) {
+        self.position = position;
+    }
+
     pub fn contents(&self, index: usize) -> Option<&str> {
         self.contents.get(index).map(|x| x.as_str())
     }
 
+    /// Value of a column, where column `0` is the index column
+    pub fn value(&self, column: usize) -> Option<&str> {
+        if column == 0 {
+            Some(&self.key)
+        } else {
+            self.contents(column - 1)
+        }
+    }
+
     pub fn iter_contents(&self) -> impl Iterator<Item = &str> {
         self.contents.iter().map(|s| &s[..])
     }
+
+    /// Words of a column which mix scripts or look like other words, where
+    /// column `0` is the index column
+    pub fn homoglyphs(&self, column: usize) -> &[Homoglyph] {
+        self.homoglyphs
+            .iter()
+            .find(|(c, _)| *c == column)
+            .map(|(_, homoglyphs)| &homoglyphs[..])
+            .unwrap_or_default()
+    }
+}
+
+fn homoglyphs_by_column(key: &str, contents: &[String]) -> Vec<(usize, Vec<Homoglyph>)> {
+    std::iter::once(key)
+        .chain(contents.iter().map(String::as_str))
+        .enumerate()
+        .map(|(column, value)| (column, find_homoglyphs(value)))
+        .filter(|(_, homoglyphs)| !homoglyphs.is_empty())
+        .collect()
 }
 
 impl TryFrom<StringRecord> for LogLine {
@@ -40,8 +118,12 @@ impl TryFrom<StringRecord> for LogLine {
 
         let key = contents.remove(0);
         Ok(Self {
+            homoglyphs: homoglyphs_by_column(&key, &contents),
             key,
+            timestamp: None,
             contents,
+            position: None,
+            unparsed: false,
         })
     }
 }
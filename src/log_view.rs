@@ -1,64 +1,341 @@
 use ratatui::{
-    layout::{Constraint, Layout, Margin},
-    widgets::{Borders, List, StatefulWidget, Table},
+    buffer::Buffer,
+    layout::{Constraint, Layout, Margin, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span, Text},
+    widgets::{
+        Borders, Clear, List, ListItem, ListState, StatefulWidget, Table, TableState, Widget,
+    },
 };
 
-use crate::{tui_helper::WithBorders, LogData, LogViewState};
+use crate::{
+    tui_helper::WithBorders, wrapped_height, AsMasked, LogData, LogViewState, TimestampDisplay,
+    WrapLayout,
+};
 
 pub struct LogView<'d> {
     data: &'d dyn LogData,
+
+    /// indices of the records which are visible
+    rows: &'d [usize],
     mask_unicode: bool,
+    timestamp_display: TimestampDisplay,
+
+    /// returns the symbol to display left of the index column of a record
+    gutter: Option<&'d dyn Fn(usize) -> Option<Span<'static>>>,
+
+    /// first and last position of the selected rows
+    selection: Option<(usize, usize)>,
+
+    /// returns the unparsed line of a record, if it shall be displayed
+    /// instead of the columns
+    raw_lines: Option<&'d dyn Fn(usize) -> Option<String>>,
+
+    /// display long rows over multiple lines instead of cutting them
+    wrap: bool,
 }
 
-impl<'d> From<&'d dyn LogData> for LogView<'d> {
-    fn from(data: &'d dyn LogData) -> Self {
+impl<'d> LogView<'d> {
+    pub fn new(data: &'d dyn LogData, rows: &'d [usize]) -> Self {
         Self {
             data,
+            rows,
             mask_unicode: false,
+            timestamp_display: TimestampDisplay::default(),
+            gutter: None,
+            selection: None,
+            raw_lines: None,
+            wrap: false,
         }
     }
-}
 
-impl<'d> LogView<'d> {
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
     pub fn with_mask_unicode(mut self, mask_unicode: bool) -> Self {
         self.mask_unicode = mask_unicode;
         self
     }
-}
 
-impl<'d> StatefulWidget for LogView<'d> {
-    type State = LogViewState;
+    pub fn with_gutter(mut self, gutter: &'d dyn Fn(usize) -> Option<Span<'static>>) -> Self {
+        self.gutter = Some(gutter);
+        self
+    }
+
+    pub fn with_selection(mut self, selection: Option<(usize, usize)>) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    pub fn with_raw_lines(mut self, raw_lines: &'d dyn Fn(usize) -> Option<String>) -> Self {
+        self.raw_lines = Some(raw_lines);
+        self
+    }
+
+    pub fn with_timestamp_display(mut self, timestamp_display: TimestampDisplay) -> Self {
+        self.timestamp_display = timestamp_display;
+        self
+    }
+
+    /// Returns the line which is displayed across the data area instead of
+    /// the columns of a record: its unparsed line in the raw view, or the
+    /// line itself if it could not be split into columns
+    fn raw_line(&self, idx: usize) -> Option<String> {
+        self.raw_lines
+            .and_then(|raw_lines| raw_lines(idx))
+            .or_else(|| self.data.unparsed_line(idx).map(String::from))
+    }
 
-    fn render(
-        self,
-        area: ratatui::prelude::Rect,
-        buf: &mut ratatui::prelude::Buffer,
-        state: &mut Self::State,
-    ) {
+    /// Splits the area into the gutter, the index column and the data area
+    fn split(&self, area: Rect) -> (Rect, Rect, Rect) {
         let margin = Margin::new(0, 0);
 
-        let index_width =
-            u16::try_from(*self.data.index_info().width()).unwrap() + 2 * margin.horizontal + 1; // add 1 to have space for the right border
+        let index_width = u16::max(
+            u16::try_from(*self.data.index_info().width()).unwrap_or(u16::MAX),
+            u16::try_from(self.timestamp_display.width()).unwrap_or(u16::MAX),
+        )
+        .saturating_add(2 * margin.horizontal + 1); // add 1 to have space for the right border
+
+        let gutter_width = if self.gutter.is_some() { 1 } else { 0 };
+        let parts = Layout::horizontal(vec![
+            Constraint::Length(gutter_width),
+            Constraint::Length(index_width),
+            Constraint::Min(1),
+        ])
+        .split(area);
+        (parts[0], parts[1].inner(&margin), parts[2].inner(&margin))
+    }
+
+    /// Renders every row over as many lines as its longest value needs
+    fn render_wrapped(self, area: Rect, buf: &mut Buffer, state: &mut LogViewState) {
+        let (gutter_part, index_part, data_part) = self.split(area);
+        state.set_data_width(data_part.width);
+        let layout = WrapLayout::new(self.data, data_part.width);
+        let raw_line = |idx: usize| self.raw_line(idx);
+        let wrap = |position: usize| {
+            self.rows
+                .get(position)
+                .map(|idx| {
+                    layout.wrap(
+                        self.data,
+                        *idx,
+                        raw_line(*idx).as_deref(),
+                        self.mask_unicode,
+                    )
+                })
+                .unwrap_or_default()
+        };
+        state.scroll_to_cursor_wrapped(data_part.height.into(), |position| {
+            wrapped_height(&wrap(position))
+        });
+
+        // vertical position and height of every visible row
+        let mut positions = Vec::new();
+        let mut gutter_items = Vec::new();
+        let mut index_items = Vec::new();
+        let mut y = 0;
+        let mut position = *state.vscroll_offset();
+        while y < data_part.height && position < self.rows.len() {
+            let idx = self.rows[position];
+            let cells = wrap(position);
+            let height = u16::try_from(wrapped_height(&cells)).unwrap_or(u16::MAX);
+            // a row at the bottom might not fit completely
+            let visible_height = height.min(data_part.height - y);
+            let fill = vec![Line::raw(""); usize::from(visible_height) - 1];
+
+            if let Some(gutter) = self.gutter {
+                let symbol = gutter(idx).unwrap_or_else(|| Span::raw(" "));
+                let lines = std::iter::once(Line::from(symbol)).chain(fill.clone());
+                gutter_items.push(ListItem::new(Text::from_iter(lines)));
+            }
+            if let Some(line) = self.data.line(idx) {
+                let index = self.timestamp_display.index_line(line, self.mask_unicode);
+                let lines = std::iter::once(index).chain(fill);
+                index_items.push(ListItem::new(Text::from_iter(lines)));
+            }
+
+            let offsets: Vec<_> = if cells.len() == 1 {
+                vec![(0, usize::from(data_part.width))]
+            } else {
+                layout.offsets().collect()
+            };
+            for (lines, (x, width)) in cells.iter().zip(offsets) {
+                let Some(x) = u16::try_from(x).ok().filter(|x| *x < data_part.width) else {
+                    break;
+                };
+                let width = u16::try_from(width)
+                    .unwrap_or(u16::MAX)
+                    .min(data_part.width - x);
+                for (line, dy) in lines.iter().zip(y..data_part.height) {
+                    buf.set_line(data_part.x + x, data_part.y + dy, line, width);
+                }
+            }
+            positions.push((position, y, height));
+            y = y.saturating_add(height);
+            position += 1;
+        }
 
-        let parts = Layout::horizontal(vec![Constraint::Length(index_width), Constraint::Min(1)])
-            .split(area);
-        let index_part = parts[0].inner(&margin);
-        let data_part = parts[1].inner(&margin);
+        let selected = state
+            .cursor()
+            .checked_sub(*state.vscroll_offset())
+            .filter(|row| *row < positions.len());
+        if self.gutter.is_some() {
+            Widget::render(List::new(gutter_items), gutter_part, buf);
+        }
+        StatefulWidget::render(
+            List::new(index_items)
+                .with_borders(Borders::RIGHT)
+                .highlight_style(Style::new().reversed()),
+            index_part,
+            buf,
+            &mut ListState::default().with_selected(selected),
+        );
 
-        let index_list = List::new(self.data.index_rows(&state.viewport(&index_part), self.mask_unicode))
-            .with_borders(Borders::RIGHT);
+        for (position, y, height) in positions {
+            let row =
+                |part: Rect| Rect::new(part.x, part.y + y, part.width, height).intersection(part);
+            if self
+                .selection
+                .is_some_and(|(first, last)| (first..=last).contains(&position))
+            {
+                buf.set_style(row(area), Style::new().on_blue());
+            } else if position == *state.cursor() {
+                buf.set_style(row(data_part), Style::new().reversed());
+            }
 
+            // underline the focused cell
+            if position == *state.cursor() {
+                let focused_cell = if *state.column() == 0 {
+                    Rect::new(index_part.x, index_part.y + y, index_part.width, 1)
+                } else {
+                    let (x, width) = layout
+                        .offsets()
+                        .nth(state.column() - 1)
+                        .map(|(x, width)| {
+                            (
+                                u16::try_from(x).unwrap_or(u16::MAX),
+                                u16::try_from(width).unwrap_or(u16::MAX),
+                            )
+                        })
+                        .unwrap_or((0, data_part.width));
+                    Rect::new(
+                        data_part.x.saturating_add(x),
+                        data_part.y + y,
+                        width,
+                        height,
+                    )
+                };
+                buf.set_style(
+                    focused_cell.intersection(data_part.union(index_part)),
+                    Style::new().underlined().bold(),
+                );
+            }
+        }
+    }
+}
+
+impl<'d> StatefulWidget for LogView<'d> {
+    type State = LogViewState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        if self.wrap {
+            return self.render_wrapped(area, buf, state);
+        }
+        let (gutter_part, index_part, data_part) = self.split(area);
+        state.set_data_width(data_part.width);
+
+        state.scroll_to_cursor(data_part.height.into());
         let data_viewport = state.viewport(&data_part);
+        let rows = &self.rows[usize::min(data_viewport.vbegin(), self.rows.len())
+            ..usize::min(data_viewport.vend(), self.rows.len())];
+        let selected = state
+            .cursor()
+            .checked_sub(data_viewport.vbegin())
+            .filter(|row| *row < rows.len());
+
+        let index_list = List::new(self.data.index_rows(
+            rows,
+            self.mask_unicode,
+            self.timestamp_display,
+        ))
+        .with_borders(Borders::RIGHT)
+        .highlight_style(Style::new().reversed());
+
+        let first_column_width = self.data.data_widths(&data_viewport).next();
         let data_table = Table::new(
-            self.data.data_rows(&data_viewport, self.mask_unicode),
+            self.data.data_rows(rows, &data_viewport, self.mask_unicode),
             self.data
                 .data_widths(&data_viewport)
-                .map(|n| u16::try_from(n).unwrap())
+                .map(|n| u16::try_from(n).unwrap_or(u16::MAX))
                 .map(Constraint::Min),
         )
-        .with_borders(Borders::NONE);
+        .with_borders(Borders::NONE)
+        .highlight_style(Style::new().reversed());
 
-        ratatui::widgets::Widget::render(index_list, index_part, buf);
-        ratatui::widgets::Widget::render(data_table, data_part, buf);
+        if let Some(gutter) = self.gutter {
+            let gutter_list = List::new(
+                rows.iter()
+                    .map(|idx| ListItem::new(gutter(*idx).unwrap_or_else(|| Span::raw(" ")))),
+            );
+            Widget::render(gutter_list, gutter_part, buf);
+        }
+        StatefulWidget::render(
+            index_list,
+            index_part,
+            buf,
+            &mut ListState::default().with_selected(selected),
+        );
+        StatefulWidget::render(
+            data_table,
+            data_part,
+            buf,
+            &mut TableState::default().with_selected(selected),
+        );
+
+        // replace the columns of raw rows by the unparsed line
+        let hoffset = *data_viewport.hoffset();
+        for (position, idx) in rows.iter().enumerate() {
+            let (Some(raw), Ok(y)) = (self.raw_line(*idx), u16::try_from(position)) else {
+                continue;
+            };
+            let row =
+                Rect::new(data_part.x, data_part.y + y, data_part.width, 1).intersection(data_part);
+            Clear.render(row, buf);
+            let line = raw.as_masked(hoffset.., self.mask_unicode, &[]).italic();
+            buf.set_line(row.x, row.y, &line, row.width);
+            if selected == Some(position) {
+                buf.set_style(row, Style::new().reversed());
+            }
+        }
+
+        // highlight the selected rows
+        if let Some((first, last)) = self.selection {
+            let first = usize::max(first, data_viewport.vbegin());
+            let last = usize::min(last + 1, data_viewport.vbegin() + rows.len());
+            for position in first..last {
+                if let Ok(y) = u16::try_from(position - data_viewport.vbegin()) {
+                    let row = Rect::new(area.x, area.y + y, area.width, 1);
+                    buf.set_style(row.intersection(area), Style::new().on_blue());
+                }
+            }
+        }
+
+        // underline the focused cell
+        if let Some(selected) = selected.and_then(|s| u16::try_from(s).ok()) {
+            let focused_cell = if *state.column() == 0 {
+                Rect::new(index_part.x, index_part.y + selected, index_part.width, 1)
+            } else {
+                let width = first_column_width
+                    .and_then(|w| u16::try_from(w).ok())
+                    .unwrap_or(data_part.width);
+                Rect::new(data_part.x, data_part.y + selected, width, 1)
+            };
+            buf.set_style(
+                focused_cell.intersection(area),
+                Style::new().underlined().bold(),
+            );
+        }
     }
 }
@@ -1,18 +1,60 @@
 use getset::{Getters, Setters};
 use ratatui::layout::Rect;
+use serde::{Deserialize, Serialize};
 
 use crate::ViewPort;
 
 
-#[derive(Debug, Default, Clone, Copy, Getters, Setters)]
+#[derive(Debug, Default, Clone, Copy, Getters, Setters, Serialize, Deserialize)]
 #[getset(get = "pub", set = "pub")]
 pub struct LogViewState {
     vscroll_offset: usize,
     hscroll_offset: usize,
+
+    /// position of the selected row, relative to the visible rows
+    cursor: usize,
+
+    /// the focused column, where `0` is the index column
+    column: usize,
+
+    /// width of the data area when the view has been rendered the last time,
+    /// which is needed to wrap rows
+    #[serde(skip)]
+    data_width: u16,
 }
 
 impl LogViewState {
     pub fn viewport(&self, area: &Rect) -> ViewPort {
         ViewPort::from_rect(self.hscroll_offset, self.vscroll_offset, area)
     }
-}
\ No newline at end of file
+
+    /// Scrolls vertically so that the cursor is inside a view of `height` rows
+    pub fn scroll_to_cursor(&mut self, height: usize) {
+        if self.cursor < self.vscroll_offset {
+            self.vscroll_offset = self.cursor;
+        } else if height > 0 && self.cursor >= self.vscroll_offset + height {
+            self.vscroll_offset = self.cursor + 1 - height;
+        }
+    }
+
+    /// Scrolls vertically so that the cursor is inside a view of `height`
+    /// terminal lines, where `row_height` returns the number of lines of the
+    /// row at a position
+    pub fn scroll_to_cursor_wrapped(&mut self, height: usize, row_height: impl Fn(usize) -> usize) {
+        if self.cursor < self.vscroll_offset {
+            self.vscroll_offset = self.cursor;
+            return;
+        }
+        let mut first = self.cursor;
+        let mut lines = row_height(first);
+        while first > self.vscroll_offset {
+            let previous = row_height(first - 1);
+            if lines + previous > height {
+                break;
+            }
+            lines += previous;
+            first -= 1;
+        }
+        self.vscroll_offset = first;
+    }
+}
@@ -2,39 +2,87 @@ use clap::Parser;
 use loghawk::app::{App, AppResult};
 use loghawk::cli::Cli;
 use loghawk::event::{Event, EventHandler};
-use loghawk::handler::handle_key_events;
-use loghawk::tui::Tui;
-use std::io;
+use loghawk::handler::{handle_key_events, handle_mouse_events, handle_paste_event};
+use loghawk::is_broken_pipe;
+use loghawk::tui::{self, Tui};
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
 
 fn main() -> AppResult<()> {
     let cli = Cli::parse();
 
+    // Decide whether the rows are displayed before the files are loaded, so
+    // that no session is restored if they are only written.
+    let batch = cli.is_batch();
+
     // Create an application.
     let mut app = App::new(cli)?;
 
+    // Write the rows without entering raw mode, if they are not displayed or
+    // if there is no terminal to display them on.
+    if batch {
+        if let Err(why) = app.print() {
+            // the reader of the output, e.g. `head`, may stop reading early
+            if !is_broken_pipe(&why) {
+                return Err(why.into());
+            }
+        }
+        return Ok(());
+    }
+
     // Initialize the terminal user interface.
-    let backend = CrosstermBackend::new(io::stderr());
+    let backend = CrosstermBackend::new(tui::terminal_output()?);
     let terminal = Terminal::new(backend)?;
     let events = EventHandler::new(250);
     let mut tui = Tui::new(terminal, events);
     tui.init()?;
 
     // Start the main loop.
+    let mut fatal_error = None;
     while *app.running() {
-        // Render the user interface.
-        tui.draw(&mut app)?;
-        // Handle events.
-        match tui.events.next()? {
-            Event::Tick => app.tick(),
-            Event::Key(key_event) => handle_key_events(key_event, &mut app)?,
-            Event::Mouse(_) => {}
-            Event::Resize(_, _) => {}
+        // Render the user interface. If this fails, the terminal cannot be
+        // used anymore, but the session is still saved.
+        if let Err(why) = tui.draw(&mut app) {
+            fatal_error = Some(format!("unable to draw the user interface: {why}"));
+            break;
+        }
+        let event = match tui.events.next() {
+            Ok(event) => event,
+            Err(why) => {
+                fatal_error = Some(format!("unable to read from the terminal: {why}"));
+                break;
+            }
+        };
+        // Handle events. Errors are displayed, so that the session can go on.
+        let result = match event {
+            Event::Tick => {
+                app.tick();
+                Ok(())
+            }
+            Event::Key(key_event) => handle_key_events(key_event, &mut app),
+            Event::Mouse(mouse_event) => handle_mouse_events(mouse_event, &mut app),
+            Event::Resize(_, _) => Ok(()),
+            Event::Paste(text) => handle_paste_event(text, &mut app),
+            Event::Error(why) => {
+                fatal_error = Some(format!("unable to read from the terminal: {why}"));
+                app.quit();
+                Ok(())
+            }
+        };
+        if let Err(why) = result {
+            app.show_error(why);
         }
     }
 
-    // Exit the user interface.
-    tui.exit()?;
-    Ok(())
+    // Exit the user interface, and save the sessions even if the terminal
+    // cannot be restored.
+    let exit_result = tui.exit();
+
+    if let Err(why) = app.save_sessions() {
+        eprintln!("unable to save the session: {why}");
+    }
+    match fatal_error {
+        Some(why) => Err(why.into()),
+        None => exit_result,
+    }
 }
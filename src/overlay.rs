@@ -0,0 +1,240 @@
+use getset::Getters;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{
+        Block, BorderType, Clear, HighlightSpacing, List, ListItem, ListState, Paragraph,
+        StatefulWidget, Widget,
+    },
+};
+
+use crate::RowFilter;
+
+/// What happens if the user selects an item of an [`Overlay`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OverlayAction {
+    /// the item is only informational
+    None,
+
+    /// scroll the log view to the record with this index
+    JumpToRecord(usize),
+
+    /// scroll the log view to a record and focus a column
+    JumpToCell { record: usize, column: usize },
+
+    /// restrict the visible rows of the log view
+    Filter(RowFilter),
+}
+
+#[derive(Clone, Debug, Getters)]
+#[getset(get = "pub")]
+pub struct OverlayItem {
+    text: String,
+    action: OverlayAction,
+
+    /// the item is the title of the items which follow it
+    #[getset(skip)]
+    heading: bool,
+}
+
+impl OverlayItem {
+    pub fn new(text: String, action: OverlayAction) -> Self {
+        Self {
+            text,
+            action,
+            heading: false,
+        }
+    }
+
+    /// Creates an item which is the title of the items which follow it
+    pub fn heading(text: String) -> Self {
+        Self {
+            text,
+            action: OverlayAction::None,
+            heading: true,
+        }
+    }
+}
+
+/// A popup which displays a list of items, from which the user can select
+/// one to trigger its action
+#[derive(Debug, Getters)]
+#[getset(get = "pub")]
+pub struct Overlay {
+    title: String,
+
+    /// informational lines which are displayed above the items
+    header: Vec<String>,
+    items: Vec<OverlayItem>,
+    state: ListState,
+
+    /// text which the displayed items must contain, if the items can be
+    /// filtered by typing
+    filter: Option<String>,
+
+    /// indices of the items which match the filter
+    #[getset(skip)]
+    visible: Vec<usize>,
+}
+
+impl Overlay {
+    pub fn new(title: String, items: Vec<OverlayItem>) -> Self {
+        let mut overlay = Self {
+            title,
+            header: Vec::new(),
+            items,
+            state: ListState::default(),
+            filter: None,
+            visible: Vec::new(),
+        };
+        overlay.update_visible();
+        overlay
+    }
+
+    /// Allows the user to filter the items by typing
+    pub fn with_filter(mut self) -> Self {
+        self.filter = Some(String::new());
+        self
+    }
+
+    pub fn is_filterable(&self) -> bool {
+        self.filter.is_some()
+    }
+
+    pub fn push_filter(&mut self, c: char) {
+        if let Some(filter) = self.filter.as_mut() {
+            filter.push(c);
+            self.update_visible();
+        }
+    }
+
+    pub fn pop_filter(&mut self) {
+        if let Some(filter) = self.filter.as_mut() {
+            filter.pop();
+            self.update_visible();
+        }
+    }
+
+    /// Removes the filter text; returns `false` if there was none
+    pub fn clear_filter(&mut self) -> bool {
+        match self.filter.as_mut() {
+            Some(filter) if !filter.is_empty() => {
+                filter.clear();
+                self.update_visible();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Finds the items which contain the filter text, ignoring case.
+    /// Headings are kept if any of their items matches, and all items of a
+    /// heading match if the heading does.
+    fn update_visible(&mut self) {
+        let filter = self.filter.as_deref().unwrap_or_default().to_lowercase();
+        let mut heading = None;
+        let mut heading_matches = false;
+        self.visible.clear();
+        for (idx, item) in self.items.iter().enumerate() {
+            if item.heading {
+                heading = Some(idx);
+                heading_matches = item.text.to_lowercase().contains(&filter);
+            } else if heading_matches || item.text.to_lowercase().contains(&filter) {
+                self.visible.extend(heading.take());
+                self.visible.push(idx);
+            }
+        }
+        let first = self
+            .visible
+            .iter()
+            .position(|idx| !self.items[*idx].heading);
+        self.state = ListState::default().with_selected(first);
+    }
+
+    pub fn with_header(mut self, header: Vec<String>) -> Self {
+        self.header = header;
+        self
+    }
+
+    pub fn selected_item(&self) -> Option<&OverlayItem> {
+        self.state
+            .selected()
+            .and_then(|idx| self.visible.get(idx))
+            .and_then(|idx| self.items.get(*idx))
+    }
+
+    pub fn select_next(&mut self, steps: usize) {
+        if let Some(selected) = self.state.selected() {
+            self.state
+                .select(Some(usize::min(selected + steps, self.visible.len() - 1)));
+        }
+    }
+
+    pub fn select_previous(&mut self, steps: usize) {
+        if let Some(selected) = self.state.selected() {
+            self.state.select(Some(selected.saturating_sub(steps)));
+        }
+    }
+}
+
+impl Widget for &mut Overlay {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [_, area, _] = Layout::vertical([
+            Constraint::Percentage(15),
+            Constraint::Percentage(70),
+            Constraint::Percentage(15),
+        ])
+        .areas(area);
+        let [_, area, _] = Layout::horizontal([
+            Constraint::Percentage(10),
+            Constraint::Percentage(80),
+            Constraint::Percentage(10),
+        ])
+        .areas(area);
+
+        let count = self
+            .visible
+            .iter()
+            .filter(|idx| !self.items[**idx].heading)
+            .count();
+        let bottom_title = match &self.filter {
+            Some(filter) if !filter.is_empty() => format!(" filter: {filter} "),
+            Some(_) => " type to filter, Esc: close ".into(),
+            None => " Enter: select, Esc: close ".into(),
+        };
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title(format!(" {} ({count}) ", self.title))
+            .title_bottom(bottom_title);
+        let list = List::new(self.visible.iter().map(|idx| {
+            let item = &self.items[*idx];
+            if item.heading {
+                ListItem::new(item.text.as_str()).bold().underlined()
+            } else {
+                ListItem::new(item.text.as_str())
+            }
+        }))
+        .highlight_style(Style::new().reversed())
+        .highlight_spacing(HighlightSpacing::Always)
+        .highlight_symbol("> ");
+
+        Clear.render(area, buf);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let header_height = u16::try_from(self.header.len()).unwrap_or(u16::MAX);
+        let [header_area, list_area] =
+            Layout::vertical([Constraint::Length(header_height), Constraint::Min(1)]).areas(inner);
+        Paragraph::new(
+            self.header
+                .iter()
+                .map(|line| Line::raw(line.as_str()))
+                .collect::<Vec<_>>(),
+        )
+        .bold()
+        .render(header_area, buf);
+        StatefulWidget::render(list, list_area, buf, &mut self.state);
+    }
+}
@@ -0,0 +1,60 @@
+/// Which rows of the log view are displayed as they have been found in the
+/// input, instead of being split into columns
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RawView {
+    #[default]
+    Off,
+
+    /// only the row at the cursor
+    CurrentRow,
+
+    /// all rows on the screen
+    Screen,
+}
+
+impl RawView {
+    /// Returns `true` if the record at `record` must be displayed raw, where
+    /// `cursor` is the record at the cursor
+    pub fn shows(&self, record: usize, cursor: Option<usize>) -> bool {
+        match self {
+            Self::Off => false,
+            Self::CurrentRow => cursor == Some(record),
+            Self::Screen => true,
+        }
+    }
+}
+
+/// Makes line breaks inside of a raw record visible, so that it fits into
+/// one row
+pub fn escape_line_breaks(raw: &str) -> String {
+    raw.replace('\r', "\\r").replace('\n', "\\n")
+}
+
+/// Formats bytes like `xxd` does: the offset, 16 bytes in hex and the same
+/// bytes as ASCII. `offset` is the position of the first byte in the file.
+pub fn hex_dump(bytes: &[u8], offset: u64) -> Vec<String> {
+    bytes
+        .chunks(16)
+        .zip((offset..).step_by(16))
+        .map(|(chunk, offset)| {
+            let mut hex = String::new();
+            for (idx, byte) in chunk.iter().enumerate() {
+                if idx > 0 && idx % 2 == 0 {
+                    hex.push(' ');
+                }
+                hex.push_str(&format!("{byte:02x}"));
+            }
+            let ascii: String = chunk
+                .iter()
+                .map(|byte| {
+                    if byte.is_ascii_graphic() || *byte == b' ' {
+                        char::from(*byte)
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            format!("{offset:08x}: {hex:<39}  {ascii}")
+        })
+        .collect()
+}
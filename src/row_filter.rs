@@ -0,0 +1,218 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{parse_timestamp, Annotation, LogLine};
+
+/// Restricts the visible rows to those with a timestamp inside a time window
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeRange {
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+
+    /// keep rows whose index value cannot be parsed as timestamp
+    keep_unparsed: bool,
+}
+
+impl TimeRange {
+    pub fn new(
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        keep_unparsed: bool,
+    ) -> Self {
+        Self {
+            from,
+            to,
+            keep_unparsed,
+        }
+    }
+
+    /// Parses the arguments of the `range` command, which are two
+    /// timestamps separated by whitespace. Because timestamps may contain
+    /// whitespace themselves, every possible split point is tried. Use `-`
+    /// to leave one side of the range open. A range which ends before it
+    /// starts is rejected, because it would hide all rows.
+    pub fn parse(args: &str, keep_unparsed: bool) -> anyhow::Result<Self> {
+        let bound = |s: &str| match s.trim() {
+            "-" | "*" => Some(None),
+            s => parse_timestamp(s).map(Some),
+        };
+
+        let tokens: Vec<_> = args.split_whitespace().collect();
+        for split in 1..tokens.len() {
+            if let (Some(from), Some(to)) = (
+                bound(&tokens[..split].join(" ")),
+                bound(&tokens[split..].join(" ")),
+            ) {
+                if let (Some(from), Some(to)) = (from, to) {
+                    if from > to {
+                        anyhow::bail!("the range ends before it starts");
+                    }
+                }
+                return Ok(Self::new(from, to, keep_unparsed));
+            }
+        }
+        anyhow::bail!("usage: range <from> <to>, use '-' for an open end")
+    }
+
+    pub fn matches(&self, line: &LogLine) -> bool {
+        match line.timestamp() {
+            None => self.keep_unparsed,
+            Some(ts) => {
+                self.from.map(|from| *ts >= from).unwrap_or(true)
+                    && self.to.map(|to| *ts <= to).unwrap_or(true)
+            }
+        }
+    }
+}
+
+impl Display for TimeRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let bound = |ts: Option<DateTime<Utc>>| {
+            ts.map(|ts| ts.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|| "…".to_string())
+        };
+        write!(f, "{} – {}", bound(self.from), bound(self.to))
+    }
+}
+
+/// A condition which must be fulfilled by a row to be visible
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RowFilter {
+    TimeRange(TimeRange),
+
+    /// the value of the column (where `0` is the index column) must be equal
+    /// to the given value
+    ColumnEquals {
+        column: usize,
+        column_name: String,
+        value: String,
+    },
+
+    /// any column must contain the given value
+    Contains(String),
+
+    /// the record must be annotated with the given tag
+    Tag(String),
+}
+
+impl RowFilter {
+    pub fn matches(&self, line: &LogLine, annotation: Option<&Annotation>) -> bool {
+        match self {
+            Self::TimeRange(range) => range.matches(line),
+            Self::ColumnEquals { column, value, .. } => {
+                line.value(*column).unwrap_or_default() == value
+            }
+            Self::Contains(value) => {
+                line.key_value().contains(value.as_str())
+                    || line.iter_contents().any(|c| c.contains(value.as_str()))
+            }
+            Self::Tag(tag) => annotation.is_some_and(|a| a.tags().contains(tag)),
+        }
+    }
+
+    pub fn as_time_range(&self) -> Option<&TimeRange> {
+        match self {
+            Self::TimeRange(range) => Some(range),
+            _ => None,
+        }
+    }
+}
+
+impl Display for RowFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TimeRange(range) => write!(f, "range: {range}"),
+            Self::ColumnEquals {
+                column_name, value, ..
+            } => write!(f, "{column_name} = '{value}'"),
+            Self::Contains(value) => write!(f, "* ~ '{value}'"),
+            Self::Tag(tag) => write!(f, "#{tag}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeZone, Utc};
+
+    use super::TimeRange;
+    use crate::LogLine;
+
+    fn ts(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, hour, 0, 0).unwrap()
+    }
+
+    fn line(timestamp: Option<DateTime<Utc>>) -> LogLine {
+        let mut line = LogLine::new("key".into(), "value".into());
+        line.set_timestamp(timestamp);
+        line
+    }
+
+    #[test]
+    fn test_parse() {
+        let cases = [
+            (
+                "2024-03-01T10:00:00Z 2024-03-01T12:00:00Z",
+                Some(10),
+                Some(12),
+            ),
+            (
+                "2024-03-01 10:00:00 2024-03-01 12:00:00",
+                Some(10),
+                Some(12),
+            ),
+            ("2024-03-01 10:00 -", Some(10), None),
+            ("* 2024-03-01T12:00:00Z", None, Some(12)),
+            ("- -", None, None),
+            (
+                "2024-03-01T10:00:00Z 2024-03-01T10:00:00Z",
+                Some(10),
+                Some(10),
+            ),
+        ];
+        for (args, from, to) in cases {
+            let range = TimeRange::parse(args, false).unwrap();
+            assert_eq!(
+                range,
+                TimeRange::new(from.map(ts), to.map(ts), false),
+                "{args}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for args in [
+            "",
+            "-",
+            "2024-03-01T10:00:00Z",
+            "yesterday today",
+            "2024-03-01T10:00:00Z 2024-03-01T12:00:00Z 2024-03-01T14:00:00Z",
+        ] {
+            let why = TimeRange::parse(args, false).unwrap_err();
+            assert!(why.to_string().starts_with("usage:"), "{args}: {why}");
+        }
+
+        let swapped = "2024-03-01T12:00:00Z 2024-03-01T10:00:00Z";
+        let why = TimeRange::parse(swapped, false).unwrap_err();
+        assert_eq!(why.to_string(), "the range ends before it starts");
+    }
+
+    #[test]
+    fn test_matches() {
+        let args = "2024-03-01T10:00:00Z 2024-03-01T12:00:00Z";
+        let range = TimeRange::parse(args, false).unwrap();
+        assert!(!range.matches(&line(Some(ts(9)))));
+        assert!(range.matches(&line(Some(ts(10)))));
+        assert!(range.matches(&line(Some(ts(12)))));
+        assert!(!range.matches(&line(Some(ts(13)))));
+        assert!(!range.matches(&line(None)));
+
+        let range = TimeRange::parse("2024-03-01T10:00:00Z -", true).unwrap();
+        assert!(!range.matches(&line(Some(ts(9)))));
+        assert!(range.matches(&line(Some(ts(23)))));
+        assert!(range.matches(&line(None)));
+    }
+}
@@ -0,0 +1,107 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::Context;
+use getset::Getters;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{Bookmarks, LogViewState, RowFilter};
+
+/// State of a tab which is restored when the same file is opened again.
+///
+/// Only state which can be changed in the user interface is stored: the
+/// scroll offsets and the cursor, the filters and the bookmarks. Searches
+/// are kept in the command line history, which is shared by all files.
+/// Hidden columns, column widths, the key column and the sort order cannot
+/// be changed interactively yet, so they are not part of the session; they
+/// are to be added together with the commands which change them.
+#[derive(Debug, Getters, Serialize, Deserialize)]
+#[getset(get = "pub")]
+pub struct Session {
+    path: PathBuf,
+
+    /// size and modification time of the file; if any of them has changed,
+    /// the session is discarded, because the records may be different
+    size: u64,
+    modified: Option<SystemTime>,
+
+    viewstate: LogViewState,
+    filters: Vec<RowFilter>,
+    bookmarks: Bookmarks,
+}
+
+impl Session {
+    pub fn new(
+        path: &Path,
+        viewstate: LogViewState,
+        filters: Vec<RowFilter>,
+        bookmarks: Bookmarks,
+    ) -> anyhow::Result<Self> {
+        let path = path.canonicalize()?;
+        let metadata = path.metadata()?;
+        Ok(Self {
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+            path,
+            viewstate,
+            filters,
+            bookmarks,
+        })
+    }
+
+    /// Reads the session of a file. Returns `None` if there is no session
+    /// or if the file has been changed since the session has been saved.
+    pub fn load(path: &Path) -> anyhow::Result<Option<Self>> {
+        let path = path.canonicalize()?;
+        let session_file = session_file(&path)?;
+        if !session_file.exists() {
+            return Ok(None);
+        }
+
+        let reader = BufReader::new(File::open(&session_file)?);
+        let session: Self = serde_json::from_reader(reader)
+            .with_context(|| format!("invalid session file: {}", session_file.display()))?;
+
+        let metadata = path.metadata()?;
+        if session.path != path
+            || session.size != metadata.len()
+            || session.modified != metadata.modified().ok()
+        {
+            return Ok(None);
+        }
+        Ok(Some(session))
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let session_file = session_file(&self.path)?;
+        if let Some(parent) = session_file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let writer = BufWriter::new(
+            File::create(&session_file)
+                .with_context(|| format!("unable to write {}", session_file.display()))?,
+        );
+        serde_json::to_writer(writer, self)?;
+        Ok(())
+    }
+}
+
+/// Sessions are stored in the local data directory of the user, in a file
+/// whose name is derived from the path of the input file
+fn session_file(path: &Path) -> anyhow::Result<PathBuf> {
+    let data_dir = dirs::data_local_dir()
+        .ok_or_else(|| anyhow::anyhow!("unable to find the local data directory"))?;
+    let hash: String = Sha256::digest(path.as_os_str().as_encoded_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    Ok(data_dir
+        .join("loghawk")
+        .join("sessions")
+        .join(format!("{hash}.json")))
+}
@@ -0,0 +1,13 @@
+/// Arrangement of the panes in the main area
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SplitLayout {
+    /// only the active tab is visible
+    #[default]
+    Single,
+
+    /// the panes are stacked on top of each other
+    Horizontal,
+
+    /// the panes are displayed side by side
+    Vertical,
+}
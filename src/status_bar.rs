@@ -0,0 +1,118 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget},
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::Tab;
+
+/// Displays information about the active tab, together with a message for
+/// the user
+pub struct StatusBar<'a> {
+    tab: &'a Tab,
+    message: Option<&'a str>,
+    error: Option<&'a str>,
+}
+
+impl<'a> From<&'a Tab> for StatusBar<'a> {
+    fn from(tab: &'a Tab) -> Self {
+        Self {
+            tab,
+            message: None,
+            error: None,
+        }
+    }
+}
+
+impl<'a> StatusBar<'a> {
+    pub fn with_message(mut self, message: Option<&'a str>) -> Self {
+        self.message = message;
+        self
+    }
+
+    /// Displays an error instead of the message
+    pub fn with_error(mut self, error: Option<&'a str>) -> Self {
+        self.error = error;
+        self
+    }
+}
+
+impl<'a> Widget for StatusBar<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let data = self.tab.data();
+        let total = self.tab.visible_rows().len();
+        let row = total.min(self.tab.viewstate().cursor() + 1);
+        let percentage = (row * 100).checked_div(total).unwrap_or(100);
+        let column = data
+            .column_info(data.column_at(*self.tab.viewstate().hscroll_offset()))
+            .map(|info| info.name().as_str())
+            .unwrap_or_default();
+
+        let mut spans = vec![
+            Span::raw(format!(" {} ", self.tab.title())).bold(),
+            Span::raw(format!(
+                " {} {} ",
+                data.format_name(),
+                data.encoding().name()
+            )),
+            Span::raw(format!(" {row}/{total} {percentage}% ")),
+            Span::raw(format!(" col: {column} ")),
+        ];
+
+        for filter in self.tab.filters() {
+            spans.push(Span::raw(format!(" [{filter}] ")).yellow());
+        }
+
+        if let Some((first, last)) = self.tab.selection() {
+            spans.push(Span::raw(format!(" [{} selected] ", last + 1 - first)).cyan());
+        }
+
+        if let Some(annotation) = self
+            .tab
+            .cursor_record()
+            .and_then(|record| self.tab.annotation(record))
+        {
+            let text = std::iter::once(annotation.note().clone())
+                .filter(|note| !note.is_empty())
+                .chain(annotation.tags().iter().map(|tag| format!("#{tag}")))
+                .collect::<Vec<_>>()
+                .join(" ");
+            spans.push(Span::raw(format!(" ✎ {text} ")).magenta());
+        }
+
+        // tell what words of the focused cell look like, if they might be
+        // meant to deceive
+        let column = *self.tab.viewstate().column();
+        if let Some(line) = self
+            .tab
+            .cursor_record()
+            .and_then(|record| data.line(record))
+        {
+            for homoglyph in line.homoglyphs(column) {
+                spans.push(Span::raw(format!(" {homoglyph} ")).light_red());
+            }
+        }
+
+        let (message, message_style) = match self.error {
+            Some(error) => (Some(error), Style::new().white().on_red().bold()),
+            None => (self.message, Style::new().reversed().bold()),
+        };
+        let message_width = message
+            .map(|message| u16::try_from(message.width() + 2).unwrap_or(u16::MAX))
+            .unwrap_or_default();
+        let [info_area, message_area] =
+            Layout::horizontal([Constraint::Min(0), Constraint::Length(message_width)]).areas(area);
+
+        Paragraph::new(Line::from(spans))
+            .style(Style::new().reversed())
+            .render(info_area, buf);
+        if let Some(message) = message {
+            Paragraph::new(format!(" {message} "))
+                .style(message_style)
+                .render(message_area, buf);
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::collections::BTreeSet;
+
+use getset::Getters;
+
+use crate::{suspicious_chars, Homoglyph, LogData, SuspiciousChar};
+
+/// A value which contains characters that are used to hide or disguise
+/// text, e.g. a right-to-left override in a file name
+#[derive(Clone, Debug, Getters)]
+#[getset(get = "pub")]
+pub struct SuspiciousValue {
+    /// index of the record which contains the value
+    record: usize,
+
+    /// column of the value, where `0` is the index column
+    column: usize,
+    chars: BTreeSet<(SuspiciousChar, char)>,
+
+    /// words which mix scripts or look like ASCII words
+    homoglyphs: Vec<Homoglyph>,
+}
+
+impl SuspiciousValue {
+    /// Lists the suspicious characters and words, e.g. `bidi control
+    /// U+202E`
+    pub fn describe(&self) -> String {
+        self.chars
+            .iter()
+            .map(|(kind, ch)| format!("{kind} U+{:04X}", u32::from(*ch)))
+            .chain(self.homoglyphs.iter().map(Homoglyph::to_string))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Searches all values of all records, including those which are hidden by
+/// filters, for suspicious characters and for words which mix scripts or
+/// look like ASCII words
+pub fn find_suspicious_values(data: &dyn LogData) -> Vec<SuspiciousValue> {
+    let mut values = Vec::new();
+    for record in 0..data.len() {
+        let Some(line) = data.line(record) else {
+            continue;
+        };
+        for column in 0..data.column_count() {
+            let Some(value) = line.value(column) else {
+                continue;
+            };
+            let chars: BTreeSet<_> = suspicious_chars(value).collect();
+            let homoglyphs = line.homoglyphs(column).to_vec();
+            if !chars.is_empty() || !homoglyphs.is_empty() {
+                values.push(SuspiciousValue {
+                    record,
+                    column,
+                    chars,
+                    homoglyphs,
+                });
+            }
+        }
+    }
+    values
+}
@@ -0,0 +1,396 @@
+use std::{collections::HashSet, path::PathBuf, sync::Arc};
+
+use clio::ClioPath;
+use getset::{Getters, MutGetters, Setters};
+use ratatui::{style::Stylize, text::Span};
+
+use crate::{
+    cli::FileFormat, Annotation, Annotations, Anomaly, Bookmarks, CsvData, LogData, LogViewState,
+    RowFilter, Session, TimeRange, Timeline, TxtData,
+};
+
+/// View which has been active before a pivot, used to return to it
+#[derive(Debug)]
+struct Breadcrumb {
+    filters: Vec<RowFilter>,
+    viewstate: LogViewState,
+}
+
+/// A single opened file together with its view state
+#[derive(Debug, Getters, MutGetters, Setters)]
+#[getset(get = "pub")]
+pub struct Tab {
+    title: String,
+
+    /// path of the opened file, or `None` for stdin
+    path: Option<PathBuf>,
+
+    data: Arc<dyn LogData>,
+
+    #[getset(get_mut = "pub", set = "pub")]
+    viewstate: LogViewState,
+
+    /// all filters must match for a row to be visible
+    filters: Vec<RowFilter>,
+
+    /// indices of all records which match the filters
+    visible_rows: Vec<usize>,
+
+    /// timeline of the visible rows, together with the maximum number of
+    /// buckets it has been built for
+    #[getset(skip)]
+    timeline: Option<(usize, Option<Timeline>)>,
+
+    /// views to return to, the last one is the most recent
+    #[getset(skip)]
+    breadcrumbs: Vec<Breadcrumb>,
+
+    /// results of the last anomaly detection
+    anomalies: Vec<Anomaly>,
+
+    /// records with anomalies, which are marked in the gutter
+    #[getset(skip)]
+    anomaly_records: HashSet<usize>,
+
+    /// records which could not be parsed properly, which are marked in the
+    /// gutter
+    #[getset(skip)]
+    problem_records: HashSet<usize>,
+
+    #[getset(get_mut = "pub")]
+    bookmarks: Bookmarks,
+
+    /// notes and tags, which are persisted in a sidecar file
+    annotations: Annotations,
+
+    /// record where the selection starts; the selection extends from there
+    /// to the cursor
+    #[getset(skip)]
+    selection_anchor: Option<usize>,
+}
+
+impl Tab {
+    pub fn load_from(
+        path: &ClioPath,
+        file_format: &FileFormat,
+        delimiter: char,
+    ) -> anyhow::Result<Self> {
+        let data: Arc<dyn LogData> = match file_format {
+            FileFormat::Csv => Arc::new(CsvData::try_from(path)?),
+            FileFormat::Txt => Arc::new(TxtData::load_from(path, delimiter)?),
+        };
+
+        let title = if path.is_std() {
+            "<stdin>".to_string()
+        } else {
+            path.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.to_string_lossy().to_string())
+        };
+
+        let (annotations, file_path) = if path.is_std() {
+            (Annotations::default(), None)
+        } else {
+            // a broken sidecar file must not prevent reading the log
+            let annotations = Annotations::load(path.path(), data.as_ref())
+                .unwrap_or_else(|why| Annotations::unreadable(path.path(), &why));
+            (annotations, Some(path.path().to_path_buf()))
+        };
+
+        let visible_rows = (0..data.len()).collect();
+        let problem_records = data
+            .parse_problems()
+            .iter()
+            .map(|problem| *problem.record())
+            .collect();
+
+        // focus the first data column
+        let mut viewstate = LogViewState::default();
+        viewstate.set_column(1);
+
+        Ok(Self {
+            title,
+            path: file_path,
+            data,
+            viewstate,
+            filters: Vec::new(),
+            visible_rows,
+            timeline: None,
+            breadcrumbs: Vec::new(),
+            anomalies: Vec::new(),
+            anomaly_records: HashSet::new(),
+            problem_records,
+            bookmarks: Bookmarks::default(),
+            annotations,
+            selection_anchor: None,
+        })
+    }
+
+    pub fn time_range(&self) -> Option<&TimeRange> {
+        self.filters.iter().find_map(RowFilter::as_time_range)
+    }
+
+    /// Replaces the current time range, if any
+    pub fn set_time_range(&mut self, range: Option<TimeRange>) {
+        self.filters
+            .retain(|f| !matches!(f, RowFilter::TimeRange(_)));
+        if let Some(range) = range {
+            self.filters.push(RowFilter::TimeRange(range));
+        }
+        self.apply_filters();
+    }
+
+    pub fn set_anomalies(&mut self, anomalies: Vec<Anomaly>) {
+        self.anomaly_records = anomalies.iter().map(|a| *a.record()).collect();
+        self.anomalies = anomalies;
+    }
+
+    /// Returns `true` if any record has a symbol in the gutter
+    pub fn has_gutter(&self) -> bool {
+        !self.anomaly_records.is_empty()
+            || !self.problem_records.is_empty()
+            || !self.bookmarks.is_empty()
+            || !self.annotations.is_empty()
+    }
+
+    /// Symbol which is displayed next to the index column of a record
+    pub fn gutter_symbol(&self, record: usize) -> Option<Span<'static>> {
+        if let Some(name) = self.bookmarks.mark_of(record) {
+            Some(Span::raw(name.to_string()).cyan().bold())
+        } else if self.bookmarks.is_bookmarked(record) {
+            Some(Span::raw("•").cyan().bold())
+        } else if self.annotations.get(record).is_some() {
+            Some(Span::raw("✎").magenta().bold())
+        } else if self.problem_records.contains(&record) {
+            Some(Span::raw("?").yellow().bold())
+        } else if self.anomaly_records.contains(&record) {
+            Some(Span::raw("!").red().bold())
+        } else {
+            None
+        }
+    }
+
+    /// Starts a selection at the current row, or clears the selection
+    pub fn toggle_selection(&mut self) {
+        self.selection_anchor = match self.selection_anchor {
+            Some(_) => None,
+            None => self.cursor_record(),
+        };
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selection_anchor = None;
+    }
+
+    /// First and last position of the selected rows
+    pub fn selection(&self) -> Option<(usize, usize)> {
+        let anchor = self.selection_anchor?;
+        let anchor = usize::min(
+            self.visible_rows.partition_point(|idx| *idx < anchor),
+            self.visible_rows.len().saturating_sub(1),
+        );
+        let cursor = *self.viewstate.cursor();
+        Some((usize::min(anchor, cursor), usize::max(anchor, cursor)))
+    }
+
+    /// Indices of the selected records, or of the current record if there
+    /// is no selection
+    pub fn selected_rows(&self) -> &[usize] {
+        let (first, last) = self.selection().unwrap_or_else(|| {
+            let cursor = *self.viewstate.cursor();
+            (cursor, cursor)
+        });
+        if self.visible_rows.is_empty() {
+            &[]
+        } else {
+            &self.visible_rows[first..=usize::min(last, self.visible_rows.len() - 1)]
+        }
+    }
+
+    /// Current state of the tab, which can be restored when the file is
+    /// opened again
+    pub fn session(&self) -> anyhow::Result<Option<Session>> {
+        match &self.path {
+            Some(path) => Ok(Some(Session::new(
+                path,
+                self.viewstate,
+                self.filters.clone(),
+                self.bookmarks.clone(),
+            )?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn restore_session(&mut self, session: &Session) {
+        self.filters = session.filters().clone();
+        self.bookmarks = session.bookmarks().clone();
+        self.apply_filters();
+
+        let mut viewstate = *session.viewstate();
+        viewstate.set_cursor(usize::min(
+            *viewstate.cursor(),
+            self.visible_rows.len().saturating_sub(1),
+        ));
+        self.viewstate = viewstate;
+    }
+
+    /// Modifies the annotation of a record and writes all annotations to the
+    /// sidecar file
+    pub fn annotate(
+        &mut self,
+        record: usize,
+        f: impl FnOnce(&mut Annotations, usize),
+    ) -> anyhow::Result<()> {
+        f(&mut self.annotations, record);
+        if self.filters.iter().any(|f| matches!(f, RowFilter::Tag(_))) {
+            self.apply_filters();
+        }
+        self.annotations.save(self.data.as_ref())
+    }
+
+    pub fn annotation(&self, record: usize) -> Option<&Annotation> {
+        self.annotations.get(record)
+    }
+
+    /// Position of the next visible row after the cursor which is bookmarked
+    /// or marked
+    pub fn next_bookmark(&self) -> Option<usize> {
+        let records = self.bookmarks.records();
+        let cursor = *self.viewstate.cursor();
+        self.visible_rows
+            .iter()
+            .enumerate()
+            .skip(cursor + 1)
+            .find(|(_, record)| records.contains(record))
+            .map(|(position, _)| position)
+    }
+
+    /// Position of the previous visible row before the cursor which is
+    /// bookmarked or marked
+    pub fn previous_bookmark(&self) -> Option<usize> {
+        let records = self.bookmarks.records();
+        let cursor = *self.viewstate.cursor();
+        self.visible_rows[..usize::min(cursor, self.visible_rows.len())]
+            .iter()
+            .rposition(|record| records.contains(record))
+    }
+
+    /// Scrolls the view so that the record is displayed in the first row, if
+    /// it is visible at all
+    pub fn scroll_to_record(&mut self, record: usize) -> bool {
+        match self.visible_rows.iter().position(|idx| *idx == record) {
+            Some(position) => {
+                self.viewstate.set_cursor(position);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Index of the record which is selected by the cursor
+    pub fn cursor_record(&self) -> Option<usize> {
+        self.visible_rows.get(*self.viewstate.cursor()).copied()
+    }
+
+    /// Adds a filter, which restricts the visible rows further
+    pub fn add_filter(&mut self, filter: RowFilter) {
+        self.filters.push(filter);
+        self.apply_filters();
+    }
+
+    /// Restricts the view with an additional filter, while remembering the
+    /// current view so that [`Tab::back`] can return to it
+    pub fn pivot(&mut self, filter: RowFilter) {
+        self.breadcrumbs.push(Breadcrumb {
+            filters: self.filters.clone(),
+            viewstate: self.viewstate,
+        });
+        self.add_filter(filter);
+    }
+
+    /// Returns to the view before the last pivot. Returns `false` if there
+    /// is no such view.
+    pub fn back(&mut self) -> bool {
+        match self.breadcrumbs.pop() {
+            Some(breadcrumb) => {
+                self.filters = breadcrumb.filters;
+                self.apply_filters();
+                self.viewstate = breadcrumb.viewstate;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Number of views which can be returned to
+    pub fn breadcrumb_depth(&self) -> usize {
+        self.breadcrumbs.len()
+    }
+
+    /// Removes all filters except the time range
+    pub fn clear_filters(&mut self) {
+        self.filters
+            .retain(|f| matches!(f, RowFilter::TimeRange(_)));
+        self.apply_filters();
+    }
+
+    /// Returns the timeline of the visible rows, which is only recalculated
+    /// if the visible rows or the available width have changed
+    pub fn timeline(&mut self, max_buckets: usize) -> Option<&Timeline> {
+        if !matches!(self.timeline, Some((buckets, _)) if buckets == max_buckets) {
+            let timeline = Timeline::build(self.data.as_ref(), &self.visible_rows, max_buckets);
+            self.timeline = Some((max_buckets, timeline));
+        }
+        self.timeline.as_ref().and_then(|(_, t)| t.as_ref())
+    }
+
+    /// the last timeline which has been calculated, if any
+    pub fn cached_timeline(&self) -> Option<&Timeline> {
+        self.timeline.as_ref().and_then(|(_, t)| t.as_ref())
+    }
+
+    /// Bucket of the timeline which contains the record in the first row
+    pub fn current_bucket(&self) -> Option<usize> {
+        let timeline = self.cached_timeline()?;
+        let line = self.data.line(self.cursor_record()?)?;
+        Some(timeline.bucket_of(line.timestamp()?))
+    }
+
+    /// Returns the position of the first visible row whose timestamp is in
+    /// the given bucket of the timeline
+    pub fn first_row_in_bucket(&self, bucket: usize) -> Option<usize> {
+        let timeline = self.cached_timeline()?;
+        self.visible_rows.iter().position(|idx| {
+            self.data
+                .line(*idx)
+                .and_then(|line| line.timestamp())
+                .map(|ts| timeline.bucket_of(ts) == bucket)
+                .unwrap_or(false)
+        })
+    }
+
+    /// Recalculates the visible rows. The cursor is moved to the first
+    /// visible record at or after the record which has been selected before.
+    fn apply_filters(&mut self) {
+        let cursor_record = self.cursor_record().unwrap_or_default();
+
+        self.visible_rows = (0..self.data.len())
+            .filter(|idx| match self.data.line(*idx) {
+                Some(line) => {
+                    let annotation = self.annotations.get(*idx);
+                    self.filters.iter().all(|f| f.matches(line, annotation))
+                }
+                None => false,
+            })
+            .collect();
+        self.timeline = None;
+
+        let position = self
+            .visible_rows
+            .partition_point(|idx| *idx < cursor_record);
+        self.viewstate.set_cursor(usize::min(
+            position,
+            self.visible_rows.len().saturating_sub(1),
+        ));
+    }
+}
@@ -0,0 +1,168 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use getset::Getters;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Bar, BarChart, BarGroup, Paragraph, Widget},
+};
+
+use crate::LogData;
+
+/// bucket sizes in seconds, from which the smallest one is chosen which
+/// lets the whole time span fit into the available width
+const BUCKET_SIZES: [i64; 19] = [
+    1,
+    5,
+    10,
+    30,
+    60,
+    5 * 60,
+    10 * 60,
+    15 * 60,
+    30 * 60,
+    3600,
+    3 * 3600,
+    6 * 3600,
+    12 * 3600,
+    86400,
+    7 * 86400,
+    30 * 86400,
+    91 * 86400,
+    365 * 86400,
+    3650 * 86400,
+];
+
+/// Number of events per time bucket
+#[derive(Debug, Getters)]
+#[getset(get = "pub")]
+pub struct Timeline {
+    start: DateTime<Utc>,
+    bucket_size: TimeDelta,
+    counts: Vec<u64>,
+}
+
+impl Timeline {
+    /// Counts the timestamps of the given rows, using not more than
+    /// `max_buckets` buckets. Returns `None` if no row has a timestamp.
+    pub fn build(data: &dyn LogData, rows: &[usize], max_buckets: usize) -> Option<Self> {
+        let timestamps = || {
+            rows.iter()
+                .filter_map(|idx| data.line(*idx))
+                .filter_map(|line| line.timestamp())
+        };
+
+        let min = *timestamps().min()?;
+        let max = *timestamps().max()?;
+        let span = (max - min).num_seconds() + 1;
+        let max_buckets = i64::try_from(max_buckets.max(1)).unwrap_or(i64::MAX);
+        let bucket_seconds = BUCKET_SIZES
+            .into_iter()
+            .find(|size| (span + size - 1) / size <= max_buckets)
+            .unwrap_or(BUCKET_SIZES[BUCKET_SIZES.len() - 1]);
+        let bucket_size = TimeDelta::seconds(bucket_seconds);
+
+        // align the first bucket to a multiple of the bucket size
+        let start = DateTime::from_timestamp(
+            min.timestamp() - min.timestamp().rem_euclid(bucket_seconds),
+            0,
+        )?;
+
+        let mut timeline = Self {
+            start,
+            bucket_size,
+            counts: Vec::new(),
+        };
+        timeline.counts = vec![0; timeline.bucket_of(&max) + 1];
+        for ts in timestamps() {
+            let bucket = timeline.bucket_of(ts);
+            timeline.counts[bucket] += 1;
+        }
+        Some(timeline)
+    }
+
+    pub fn bucket_of(&self, ts: &DateTime<Utc>) -> usize {
+        let offset = (*ts - self.start).num_seconds() / self.bucket_size.num_seconds();
+        usize::try_from(offset).unwrap_or_default()
+    }
+
+    pub fn bucket_start(&self, bucket: usize) -> DateTime<Utc> {
+        self.start + self.bucket_size * i32::try_from(bucket).unwrap_or(i32::MAX)
+    }
+
+    pub fn end(&self) -> DateTime<Utc> {
+        self.bucket_start(self.counts.len())
+    }
+}
+
+/// Renders a [`Timeline`] as bar chart, with the bucket of the current
+/// position highlighted
+pub struct TimelineView<'t> {
+    timeline: &'t Timeline,
+    current_bucket: Option<usize>,
+}
+
+impl<'t> TimelineView<'t> {
+    pub fn new(timeline: &'t Timeline, current_bucket: Option<usize>) -> Self {
+        Self {
+            timeline,
+            current_bucket,
+        }
+    }
+}
+
+impl<'t> Widget for TimelineView<'t> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [chart_area, label_area] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(area);
+
+        let bars: Vec<_> = self
+            .timeline
+            .counts()
+            .iter()
+            .enumerate()
+            .map(|(idx, count)| {
+                let bar = Bar::default().value(*count).text_value(String::new());
+                if Some(idx) == self.current_bucket {
+                    bar.style(Style::new().fg(Color::Yellow))
+                } else {
+                    bar
+                }
+            })
+            .collect();
+
+        BarChart::default()
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(1)
+            .bar_gap(0)
+            .bar_style(Style::new().fg(Color::Cyan))
+            .render(chart_area, buf);
+
+        let format = "%Y-%m-%d %H:%M:%S";
+        let bucket_size = self.timeline.bucket_size().num_seconds();
+        let bucket_size = match bucket_size {
+            s if s % 86400 == 0 => format!("{}d", s / 86400),
+            s if s % 3600 == 0 => format!("{}h", s / 3600),
+            s if s % 60 == 0 => format!("{}m", s / 60),
+            s => format!("{s}s"),
+        };
+        let current = self
+            .current_bucket
+            .and_then(|bucket| self.timeline.counts().get(bucket).map(|c| (bucket, c)))
+            .map(|(bucket, count)| {
+                format!(
+                    " | {}: {count} events",
+                    self.timeline.bucket_start(bucket).format(format)
+                )
+            })
+            .unwrap_or_default();
+
+        Paragraph::new(Line::from(vec![
+            Span::raw(self.timeline.start().format(format).to_string()),
+            Span::raw(format!(" – {}", self.timeline.end().format(format))),
+            Span::raw(format!(" (bucket size: {bucket_size}{current})")).dark_gray(),
+        ]))
+        .render(label_area, buf);
+    }
+}
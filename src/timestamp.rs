@@ -0,0 +1,406 @@
+use std::borrow::Cow;
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Utc};
+use chrono_tz::Tz;
+use ratatui::text::Line;
+
+use crate::{AsMasked, LogLine};
+
+/// number of index values which are used to detect the timestamp format
+const DETECTION_SAMPLES: usize = 100;
+
+/// earliest and latest year which we accept when guessing the epoch of a
+/// numeric timestamp
+const PLAUSIBLE_YEARS: std::ops::RangeInclusive<i32> = 1980..=2100;
+
+/// ISO-8601 variants without timezone, which are interpreted as UTC
+const ISO_NAIVE_FORMATS: [&str; 3] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f UTC",
+];
+
+/// ISO-8601 variants with timezone, which are not covered by RFC 3339
+const ISO_OFFSET_FORMATS: [&str; 2] = ["%Y-%m-%d %H:%M:%S%.f%:z", "%Y-%m-%d %H:%M:%S%.f %z"];
+
+const US_FORMATS: [&str; 2] = ["%m/%d/%Y %I:%M:%S %p", "%m/%d/%Y %H:%M:%S"];
+
+/// Formats of timestamps which can be found in the index column
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// ISO-8601 and RFC 3339, with or without timezone offset
+    Iso8601,
+
+    /// RFC 2822, e.g. `Tue, 1 Jul 2003 10:52:37 +0200`
+    Rfc2822,
+
+    /// syslog timestamps without year, e.g. `Jan  5 14:02:11`
+    Syslog,
+
+    /// US format, e.g. `01/05/2024 02:02:11 PM`
+    UsDate,
+
+    /// seconds since 1970-01-01
+    UnixSeconds,
+
+    /// milliseconds since 1970-01-01
+    UnixMillis,
+
+    /// microseconds since 1970-01-01
+    UnixMicros,
+
+    /// 100 nanosecond intervals since 1601-01-01
+    WindowsFiletime,
+
+    /// microseconds since 1601-01-01, used by WebKit and Chrome
+    WebKit,
+
+    /// seconds since 2001-01-01, used by macOS and iOS
+    MacAbsolute,
+}
+
+const ALL_FORMATS: [TimestampFormat; 10] = [
+    TimestampFormat::Iso8601,
+    TimestampFormat::Rfc2822,
+    TimestampFormat::Syslog,
+    TimestampFormat::UsDate,
+    TimestampFormat::UnixSeconds,
+    TimestampFormat::UnixMillis,
+    TimestampFormat::UnixMicros,
+    TimestampFormat::WindowsFiletime,
+    TimestampFormat::WebKit,
+    TimestampFormat::MacAbsolute,
+];
+
+impl TimestampFormat {
+    /// Finds the format which is able to parse most of the given values.
+    /// At least half of the values must be parseable in this format.
+    pub fn detect<'a>(values: impl Iterator<Item = &'a str>) -> Option<Self> {
+        let now = Utc::now();
+        let mut hits = [0usize; ALL_FORMATS.len()];
+        let mut samples = 0;
+        for value in values
+            .filter(|v| !v.trim().is_empty())
+            .take(DETECTION_SAMPLES)
+        {
+            samples += 1;
+            for (idx, format) in ALL_FORMATS.iter().enumerate() {
+                if format.parse(value, now).is_some() {
+                    hits[idx] += 1;
+                }
+            }
+        }
+
+        ALL_FORMATS
+            .into_iter()
+            .zip(hits)
+            .filter(|(_, hits)| *hits > 0 && *hits * 2 >= samples)
+            .max_by_key(|(format, hits)| (*hits, std::cmp::Reverse(*format as usize)))
+            .map(|(format, _)| format)
+    }
+
+    /// Parses a value in this format. Syslog timestamps have no year, so
+    /// they are taken to be the latest date which is not after `now`.
+    pub fn parse(&self, value: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let value = value.trim();
+        match self {
+            Self::Iso8601 => parse_iso8601(value),
+            Self::Rfc2822 => DateTime::parse_from_rfc2822(value)
+                .ok()
+                .map(|ts| ts.with_timezone(&Utc)),
+            Self::Syslog => parse_syslog(value, now),
+            Self::UsDate => US_FORMATS
+                .iter()
+                .find_map(|f| NaiveDateTime::parse_from_str(value, f).ok())
+                .map(|ts| ts.and_utc()),
+            Self::UnixSeconds => parse_numeric(value, 10, epoch(1970), 1_000_000_000),
+            Self::UnixMillis => parse_numeric(value, 13, epoch(1970), 1_000_000),
+            Self::UnixMicros => parse_numeric(value, 16, epoch(1970), 1_000),
+            Self::WindowsFiletime => parse_numeric(value, 18, epoch(1601), 100),
+            Self::WebKit => parse_numeric(value, 17, epoch(1601), 1_000),
+            Self::MacAbsolute => parse_numeric(value, 9, epoch(2001), 1_000_000_000),
+        }
+    }
+}
+
+fn epoch(year: i32) -> DateTime<Utc> {
+    NaiveDate::from_ymd_opt(year, 1, 1)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .expect("invalid epoch")
+        .and_utc()
+}
+
+fn parse_iso8601(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(value) {
+        return Some(ts.with_timezone(&Utc));
+    }
+    if let Some(ts) = ISO_OFFSET_FORMATS
+        .iter()
+        .find_map(|f| DateTime::parse_from_str(value, f).ok())
+    {
+        return Some(ts.with_timezone(&Utc));
+    }
+    ISO_NAIVE_FORMATS
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(value, f).ok())
+        .map(|ts| ts.and_utc())
+}
+
+/// syslog timestamps have no year, so we assume that the timestamp is not
+/// in the future. If the date does not exist in the current year, such as
+/// February 29th, it is from one of the previous years.
+fn parse_syslog(value: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    // February 29th may be up to eight years ago, e.g. in 2104
+    (0..=8).find_map(|years_ago| {
+        let year = now.year() - years_ago;
+        NaiveDateTime::parse_from_str(&format!("{year} {value}"), "%Y %b %e %H:%M:%S%.f")
+            .ok()
+            .map(|ts| ts.and_utc())
+            .filter(|ts| *ts <= now)
+    })
+}
+
+/// parses a (possibly fractional) number of ticks since `epoch`, where each
+/// tick has a length of `nanos_per_tick` nanoseconds. Because numeric formats
+/// are ambiguous, we expect a fixed number of integral digits. This limits the
+/// timestamps which are detected to 2001-09-09 until 2286-11-20 for the Unix
+/// formats, to 2004-03-03 until 2032-09-09 for Mac absolute time, and to the
+/// plausible years for Windows FILETIME and WebKit.
+fn parse_numeric(
+    value: &str,
+    digits: usize,
+    epoch: DateTime<Utc>,
+    nanos_per_tick: i64,
+) -> Option<DateTime<Utc>> {
+    let (integral, fraction) = value.split_once('.').unwrap_or((value, ""));
+    if integral.len() != digits
+        || !integral.bytes().all(|b| b.is_ascii_digit())
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let ticks: i128 = integral.parse().ok()?;
+    let mut nanos = ticks * i128::from(nanos_per_tick);
+    if !fraction.is_empty() {
+        let fraction: f64 = format!("0.{fraction}").parse().ok()?;
+        nanos += (fraction * nanos_per_tick as f64) as i128;
+    }
+
+    let seconds = i64::try_from(nanos / 1_000_000_000).ok()?;
+    let delta = TimeDelta::try_seconds(seconds)?
+        + TimeDelta::nanoseconds((nanos % 1_000_000_000) as i64);
+    let ts = epoch.checked_add_signed(delta)?;
+    if PLAUSIBLE_YEARS.contains(&ts.year()) {
+        Some(ts)
+    } else {
+        None
+    }
+}
+
+/// Parses a single timestamp in any of the supported formats. Additionally,
+/// timestamps without seconds and dates without time are accepted, which is
+/// convenient when the user enters a timestamp.
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    let now = Utc::now();
+    ALL_FORMATS
+        .iter()
+        .find_map(|f| f.parse(value, now))
+        .or_else(|| {
+            ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"]
+                .iter()
+                .find_map(|f| NaiveDateTime::parse_from_str(value, f).ok())
+                .map(|ts| ts.and_utc())
+        })
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|ts| ts.and_utc())
+        })
+}
+
+/// Detects the timestamp format of the index column and assigns the parsed
+/// timestamps to the lines
+pub fn assign_timestamps(lines: &mut [LogLine]) {
+    let format = TimestampFormat::detect(lines.iter().map(|l| l.key_value()));
+    if let Some(format) = format {
+        let now = Utc::now();
+        for line in lines.iter_mut() {
+            let timestamp = format.parse(line.key_value(), now).or_else(|| {
+                ALL_FORMATS
+                    .iter()
+                    .filter(|f| **f != format)
+                    .find_map(|f| f.parse(line.key_value(), now))
+            });
+            line.set_timestamp(timestamp);
+        }
+    }
+}
+
+/// How the index column is displayed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimestampDisplay {
+    /// display the index value as found in the file
+    #[default]
+    Original,
+
+    /// display the parsed timestamp as ISO-8601 in UTC
+    Utc,
+
+    /// display the parsed timestamp in the given timezone
+    Timezone(Tz),
+}
+
+const UTC_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.6fZ";
+const TIMEZONE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.6f%:z";
+
+impl TimestampDisplay {
+    /// Returns the next display mode. The timezone mode is only available
+    /// if a timezone has been configured.
+    pub fn next(&self, timezone: Option<Tz>) -> Self {
+        match self {
+            Self::Original => Self::Utc,
+            Self::Utc => match timezone {
+                Some(tz) => Self::Timezone(tz),
+                None => Self::Original,
+            },
+            Self::Timezone(_) => Self::Original,
+        }
+    }
+
+    /// Displays the index value of a line. Lines without a timestamp are
+    /// always displayed as they are.
+    pub fn format<'l>(&self, line: &'l LogLine) -> Cow<'l, str> {
+        match (self, line.timestamp()) {
+            (Self::Utc, Some(ts)) => Cow::Owned(ts.format(UTC_FORMAT).to_string()),
+            (Self::Timezone(tz), Some(ts)) => Cow::Owned(
+                tz.from_utc_datetime(&ts.naive_utc())
+                    .format(TIMEZONE_FORMAT)
+                    .to_string(),
+            ),
+            _ => Cow::Borrowed(line.key_value()),
+        }
+    }
+
+    /// Renders the index value of a line for the index column
+    pub fn index_line<'l>(&self, line: &'l LogLine, mask_unicode: bool) -> Line<'l> {
+        match self.format(line) {
+            Cow::Borrowed(key) => key.as_masked(.., mask_unicode, line.homoglyphs(0)),
+            Cow::Owned(timestamp) => Line::raw(timestamp),
+        }
+    }
+
+    /// Width of formatted timestamps
+    pub fn width(&self) -> usize {
+        match self {
+            Self::Original => 0,
+            Self::Utc => "2000-01-01T00:00:00.000000Z".len(),
+            Self::Timezone(_) => "2000-01-01T00:00:00.000000+00:00".len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeZone, Utc};
+
+    use super::TimestampFormat::{self, *};
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .expect("invalid expected timestamp")
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_parse() {
+        let now = utc("2024-03-01T00:00:00Z");
+        let cases = [
+            (
+                WindowsFiletime,
+                "133485408000000000",
+                "2024-01-01T00:00:00Z",
+            ),
+            (WebKit, "13348540800000000", "2024-01-01T00:00:00Z"),
+            (MacAbsolute, "725760000", "2024-01-01T00:00:00Z"),
+            (MacAbsolute, "725760000.25", "2024-01-01T00:00:00.25Z"),
+            (UnixSeconds, "1704067200", "2024-01-01T00:00:00Z"),
+            (UnixMillis, "1704067200123", "2024-01-01T00:00:00.123Z"),
+            (
+                UnixMicros,
+                "1704067200123456",
+                "2024-01-01T00:00:00.123456Z",
+            ),
+            (Syslog, "Jan  5 14:02:11", "2024-01-05T14:02:11Z"),
+            (UsDate, "01/05/2024 02:02:11 PM", "2024-01-05T14:02:11Z"),
+            (UsDate, "01/05/2024 14:02:11", "2024-01-05T14:02:11Z"),
+            (
+                Rfc2822,
+                "Tue, 1 Jul 2003 10:52:37 +0200",
+                "2003-07-01T08:52:37Z",
+            ),
+            (Iso8601, "2024-01-05T14:02:11+02:00", "2024-01-05T12:02:11Z"),
+            (Iso8601, "2024-01-05 14:02:11 +0200", "2024-01-05T12:02:11Z"),
+            (Iso8601, "2024-01-05 14:02:11.5", "2024-01-05T14:02:11.5Z"),
+            (Iso8601, "2024-01-05T14:02:11", "2024-01-05T14:02:11Z"),
+        ];
+        for (format, value, expected) in cases {
+            assert_eq!(
+                format.parse(value, now),
+                Some(utc(expected)),
+                "{value} as {format:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_numeric_digits() {
+        let now = Utc::now();
+        // too few or too many digits, or not a number
+        assert_eq!(TimestampFormat::UnixSeconds.parse("170406720", now), None);
+        assert_eq!(TimestampFormat::UnixSeconds.parse("17040672001", now), None);
+        assert_eq!(TimestampFormat::UnixSeconds.parse("17040672OO", now), None);
+        // correct number of digits, but not a plausible year
+        assert_eq!(
+            TimestampFormat::WindowsFiletime.parse("100000000000000000", now),
+            None
+        );
+    }
+
+    #[test]
+    fn test_syslog_year() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap();
+        let parse = |value| TimestampFormat::Syslog.parse(value, now);
+        assert_eq!(parse("Feb 28 10:00:00"), Some(utc("2026-02-28T10:00:00Z")));
+        // not in the future
+        assert_eq!(parse("Dec 31 23:00:00"), Some(utc("2025-12-31T23:00:00Z")));
+        // the last February 29th
+        assert_eq!(parse("Feb 29 10:00:00"), Some(utc("2024-02-29T10:00:00Z")));
+        assert_eq!(parse("Feb 30 10:00:00"), None);
+    }
+
+    #[test]
+    fn test_detect() {
+        let detect = |values: &[&str]| TimestampFormat::detect(values.iter().copied());
+        assert_eq!(
+            detect(&["1704067200", "1704067260", "invalid"]),
+            Some(TimestampFormat::UnixSeconds)
+        );
+        assert_eq!(
+            detect(&["1704067200123", "1704067260123"]),
+            Some(TimestampFormat::UnixMillis)
+        );
+        assert_eq!(
+            detect(&["2024-01-05T14:02:11Z", "2024-01-05 14:02:12"]),
+            Some(TimestampFormat::Iso8601)
+        );
+        assert_eq!(
+            detect(&["Jan  5 14:02:11", "Jan  5 14:02:12"]),
+            Some(TimestampFormat::Syslog)
+        );
+        assert_eq!(detect(&["alice", "bob", "1704067200"]), None);
+        assert_eq!(detect(&[]), None);
+    }
+}
@@ -1,11 +1,14 @@
 use crate::app::{App, AppResult};
 use crate::event::EventHandler;
 use crate::ui;
-use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use crossterm::event::{
+    DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+};
 use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
 use ratatui::backend::Backend;
 use ratatui::Terminal;
-use std::io;
+use std::fs::OpenOptions;
+use std::io::{self, IsTerminal, Write};
 use std::panic;
 
 /// Representation of a terminal user interface.
@@ -31,7 +34,12 @@ impl<B: Backend> Tui<B> {
     /// It enables the raw mode and sets terminal properties.
     pub fn init(&mut self) -> AppResult<()> {
         terminal::enable_raw_mode()?;
-        crossterm::execute!(io::stderr(), EnterAlternateScreen, EnableMouseCapture)?;
+        crossterm::execute!(
+            terminal_output()?,
+            EnterAlternateScreen,
+            EnableMouseCapture,
+            EnableBracketedPaste
+        )?;
 
         // Define a custom panic hook to reset the terminal properties.
         // This way, you won't have your terminal messed up if an unexpected error happens.
@@ -61,7 +69,12 @@ impl<B: Backend> Tui<B> {
     /// the terminal properties if unexpected errors occur.
     fn reset() -> AppResult<()> {
         terminal::disable_raw_mode()?;
-        crossterm::execute!(io::stderr(), LeaveAlternateScreen, DisableMouseCapture)?;
+        crossterm::execute!(
+            terminal_output()?,
+            LeaveAlternateScreen,
+            DisableMouseCapture,
+            DisableBracketedPaste
+        )?;
         Ok(())
     }
 
@@ -74,3 +87,32 @@ impl<B: Backend> Tui<B> {
         Ok(())
     }
 }
+
+/// Opens the terminal to render the user interface on. This is stderr, unless
+/// it has been redirected, in which case the controlling terminal is used.
+pub fn terminal_output() -> io::Result<Box<dyn Write + Send>> {
+    if io::stderr().is_terminal() {
+        Ok(Box::new(io::stderr()))
+    } else {
+        Ok(Box::new(OpenOptions::new().write(true).open(TTY_OUTPUT)?))
+    }
+}
+
+/// Returns `true` if there is a terminal to read keys from and to render the
+/// user interface on. Keys are read from the controlling terminal if stdin
+/// is used for the data.
+pub fn has_terminal() -> bool {
+    let has_input =
+        io::stdin().is_terminal() || OpenOptions::new().read(true).open(TTY_INPUT).is_ok();
+    has_input && terminal_output().is_ok()
+}
+
+/// the controlling terminal of the process
+#[cfg(unix)]
+const TTY_INPUT: &str = "/dev/tty";
+#[cfg(unix)]
+const TTY_OUTPUT: &str = "/dev/tty";
+#[cfg(windows)]
+const TTY_INPUT: &str = "CONIN$";
+#[cfg(windows)]
+const TTY_OUTPUT: &str = "CONOUT$";
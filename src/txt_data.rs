@@ -1,14 +1,14 @@
-use std::io::{BufRead, BufReader};
+use std::io::BufRead;
 
-use clio::{ClioPath, Input};
+use clio::ClioPath;
 use ratatui::{
     text::Line,
     widgets::{Cell, ListItem, Row},
 };
 
 use crate::{
-    log_line::LogLine, AsMasked, ColumnInfo, ColumnWidth, DataRows, IndexRows, IterDataColumns,
-    LogData,
+    decoding_reader, log_line::LogLine, timestamp, AsMasked, ColumnInfo, ColumnWidth, DataRows, IndexRows, IterDataColumns,
+    LogData, RawInput, RawPosition, TimestampDisplay,
 };
 
 #[derive(Debug)]
@@ -17,17 +17,21 @@ pub struct TxtData {
 
     index_info: ColumnInfo,
     contents_info: ColumnInfo,
+    raw_input: RawInput,
 }
 
 impl TxtData {
     pub fn load_from(path: &ClioPath, delimiter: char) -> anyhow::Result<Self> {
-        let input = Input::new(path.as_os_str())?;
-        let reader = BufReader::new(input);
+        let (mut reader, raw_input) = decoding_reader(path)?;
         let mut lines = Vec::new();
         let mut index_width = 0;
         let mut contents_width = 0;
-        for line in reader.lines() {
-            let line = line?;
+        let mut offset = raw_input.text_offset();
+        let mut buffer = String::new();
+        while reader.read_line(&mut buffer)? > 0 {
+            let line = buffer.trim_end_matches('\n').trim_end_matches('\r');
+            let position = RawPosition::new(offset, raw_input.encoded_length(line));
+            offset += raw_input.encoded_length(&buffer);
 
             if let Some((index, content)) = line.split_once(delimiter) {
                 let index = index.to_string();
@@ -36,21 +40,42 @@ impl TxtData {
                 index_width = usize::max(index.len(), index_width);
                 contents_width = usize::max(content.len(), contents_width);
 
-                lines.push(LogLine::new(index, content)?);
+                lines.push(LogLine::new(index, content));
             } else {
-                lines.push(LogLine::new("".into(), line)?);
+                lines.push(LogLine::new("".into(), line.to_string()));
+            }
+            if let Some(line) = lines.last_mut() {
+                line.set_position(Some(position));
+            }
+            buffer.clear();
+        }
+        // the positions are wrong if invalid bytes have been replaced
+        if raw_input.is_lossy() {
+            for line in lines.iter_mut() {
+                line.set_position(None);
             }
         }
 
+        timestamp::assign_timestamps(&mut lines);
+
         Ok(Self {
             lines,
-            index_info: ColumnInfo::new(ColumnWidth::Width(index_width)),
-            contents_info: ColumnInfo::new(ColumnWidth::Width(contents_width)),
+            index_info: ColumnInfo::new("index".into(), ColumnWidth::Width(index_width)),
+            contents_info: ColumnInfo::new("contents".into(), ColumnWidth::Width(contents_width)),
+            raw_input,
         })
     }
 }
 
 impl LogData for TxtData {
+    fn format_name(&self) -> &'static str {
+        "TXT"
+    }
+
+    fn raw_input(&self) -> &RawInput {
+        &self.raw_input
+    }
+
     fn len(&self) -> usize {
         self.lines.len()
     }
@@ -59,6 +84,10 @@ impl LogData for TxtData {
         self.lines.is_empty()
     }
 
+    fn line(&self, idx: usize) -> Option<&LogLine> {
+        self.lines.get(idx)
+    }
+
     fn index_info(&self) -> &crate::ColumnInfo {
         &self.index_info
     }
@@ -79,21 +108,29 @@ impl LogData for TxtData {
         IterDataColumns::from(vec![&self.contents_info].into_iter())
     }
 
-    fn index_rows(&self, viewport: &crate::ViewPort, mask_unicode: bool) -> crate::IndexRows<'_> {
-        let upper_bound = usize::min(self.lines.len(), viewport.vend());
+    fn index_rows<'d>(
+        &'d self,
+        rows: &'d [usize],
+        mask_unicode: bool,
+        timestamp_display: TimestampDisplay,
+    ) -> crate::IndexRows<'d> {
         IndexRows::from(
-            self.lines[viewport.vbegin()..upper_bound]
-                .iter()
-                .map(move |v| ListItem::new(v.key_value().as_masked(.., mask_unicode))),
+            rows.iter()
+                .filter_map(|idx| self.lines.get(*idx))
+                .map(move |v| ListItem::new(timestamp_display.index_line(v, mask_unicode))),
         )
     }
 
-    fn data_rows(&self, viewport: &crate::ViewPort, mask_unicode: bool) -> crate::DataRows<'_> {
-        let upper_bound = usize::min(self.lines.len(), viewport.vend());
+    fn data_rows<'d>(
+        &'d self,
+        rows: &'d [usize],
+        viewport: &crate::ViewPort,
+        mask_unicode: bool,
+    ) -> crate::DataRows<'d> {
         let hoffset = *viewport.hoffset();
         DataRows::from(
-            self.lines[viewport.vbegin()..upper_bound]
-                .iter()
+            rows.iter()
+                .filter_map(|idx| self.lines.get(*idx))
                 .map(move |v| {
                     Row::new(vec![Cell::new(match v.contents(0) {
                         None => Line::raw(""),
@@ -101,7 +138,7 @@ impl LogData for TxtData {
                             if hoffset >= line.len() {
                                 Line::raw("")
                             } else {
-                                line.as_masked(hoffset.., mask_unicode)
+                                line.as_masked(hoffset.., mask_unicode, v.homoglyphs(1))
                             }
                         }
                     })])
@@ -1,16 +1,103 @@
 use ratatui::{
-    widgets::{Block, BorderType, Borders},
+    layout::{Constraint, Layout},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, BorderType, Borders, Tabs},
     Frame,
 };
 
-use crate::app::App;
+use crate::{app::App, SplitLayout, StatusBar};
+
+const TIMELINE_HEIGHT: u16 = 7;
 
 /// Renders the user interface widgets.
 pub fn render(app: &mut App, frame: &mut Frame) {
-    let block = Block::new()
-        .borders(Borders::NONE)
-        .border_type(BorderType::Rounded);
-    app.set_page_size(block.inner(frame.size()).height);
-    app.render_log_contents(frame, block.inner(frame.size()));
-    frame.render_widget(block, frame.size());
+    let tabbar_height = if app.tabs().len() > 1 { 1 } else { 0 };
+    let bottom_height = if app.command_line().is_some() { 1 } else { 0 };
+
+    let [tabbar_area, main_area, status_area, bottom_area] = Layout::vertical([
+        Constraint::Length(tabbar_height),
+        Constraint::Min(1),
+        Constraint::Length(1),
+        Constraint::Length(bottom_height),
+    ])
+    .areas(frame.size());
+
+    if tabbar_height > 0 {
+        let tabs = Tabs::new(
+            app.tabs()
+                .iter()
+                .enumerate()
+                .map(|(idx, tab)| Line::raw(format!("{}: {}", idx + 1, tab.title()))),
+        )
+        .select(*app.active_tab())
+        .highlight_style(Style::new().reversed());
+        frame.render_widget(tabs, tabbar_area);
+    }
+
+    let main_area = if *app.show_timeline() {
+        let [timeline_area, main_area] =
+            Layout::vertical([Constraint::Length(TIMELINE_HEIGHT), Constraint::Min(1)])
+                .areas(main_area);
+        app.render_timeline(frame, timeline_area);
+        main_area
+    } else {
+        app.set_timeline_area(None);
+        main_area
+    };
+
+    match *app.split_layout() {
+        SplitLayout::Single => {
+            let block = Block::new()
+                .borders(Borders::NONE)
+                .border_type(BorderType::Rounded);
+            app.set_page_size(block.inner(main_area).height);
+            app.render_log_contents(frame, block.inner(main_area), *app.active_tab());
+            frame.render_widget(block, main_area);
+        }
+        layout => {
+            let constraints = [Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)];
+            let [mut active_area, mut secondary_area] = if layout == SplitLayout::Horizontal {
+                Layout::vertical(constraints).areas(main_area)
+            } else {
+                Layout::horizontal(constraints).areas(main_area)
+            };
+            if *app.secondary_first() {
+                std::mem::swap(&mut active_area, &mut secondary_area);
+            }
+
+            let sync = if *app.sync_by_time() { " [sync]" } else { "" };
+            let active_block = Block::bordered()
+                .border_type(BorderType::Rounded)
+                .border_style(Style::new().yellow())
+                .title(format!("{}{sync}", app.tab().title()));
+            let secondary_block = Block::bordered()
+                .border_type(BorderType::Rounded)
+                .title(app.tabs()[*app.secondary_tab()].title().clone());
+
+            app.set_page_size(active_block.inner(active_area).height);
+            app.render_log_contents(frame, active_block.inner(active_area), *app.active_tab());
+            app.render_log_contents(
+                frame,
+                secondary_block.inner(secondary_area),
+                *app.secondary_tab(),
+            );
+            frame.render_widget(active_block, active_area);
+            frame.render_widget(secondary_block, secondary_area);
+        }
+    }
+
+    frame.render_widget(
+        StatusBar::from(app.tab())
+            .with_message(app.message().as_deref())
+            .with_error(app.error().as_deref()),
+        status_area,
+    );
+    app.render_overlay(frame, main_area);
+
+    if let Some(command_line) = app.command_line() {
+        frame.render_widget(command_line, bottom_area);
+        let (x, y) = command_line.cursor_position(bottom_area);
+        frame.set_cursor(x, y);
+    }
 }
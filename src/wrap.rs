@@ -0,0 +1,307 @@
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::{AsMasked, LogData};
+
+/// space between two columns, as in [`ratatui::widgets::Table`]
+const COLUMN_SPACING: usize = 1;
+
+/// Widths of the data columns if rows are wrapped instead of being
+/// scrolled horizontally
+#[derive(Clone, Debug)]
+pub struct WrapLayout {
+    widths: Vec<usize>,
+
+    /// width of the whole data area, which is used for raw lines
+    width: usize,
+}
+
+impl WrapLayout {
+    /// Distributes `width` among the data columns. If not all columns fit,
+    /// every column is shrunk in proportion to its width.
+    pub fn new(data: &dyn LogData, width: u16) -> Self {
+        let width = usize::from(width);
+        let natural: Vec<_> = data
+            .iter_data_columns()
+            .map(|c| usize::try_from(*c.width()).unwrap_or_default().max(1))
+            .collect();
+        let available = width.saturating_sub(COLUMN_SPACING * natural.len().saturating_sub(1));
+        let total: usize = natural.iter().sum();
+        let widths = if total <= available {
+            natural
+        } else {
+            natural
+                .iter()
+                .map(|w| (w * available / total).max(1))
+                .collect()
+        };
+        Self { widths, width }
+    }
+
+    /// horizontal position of every data column
+    pub fn offsets(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.widths.iter().scan(0, |x, width| {
+            let offset = *x;
+            *x += width + COLUMN_SPACING;
+            Some((offset, *width))
+        })
+    }
+
+    /// Splits the data columns of a record into lines. If `raw` is given,
+    /// it is displayed over the whole width instead of the columns.
+    pub fn wrap(
+        &self,
+        data: &dyn LogData,
+        record: usize,
+        raw: Option<&str>,
+        mask_unicode: bool,
+    ) -> Vec<Vec<Line<'static>>> {
+        if let Some(raw) = raw {
+            return vec![wrap_line(raw.as_masked(.., mask_unicode, &[]), self.width)];
+        }
+        let Some(line) = data.line(record) else {
+            return Vec::new();
+        };
+        line.iter_contents()
+            .zip(self.widths.iter())
+            .enumerate()
+            .map(|(idx, (value, width))| {
+                let homoglyphs = line.homoglyphs(idx + 1);
+                wrap_line(value.as_masked(.., mask_unicode, homoglyphs), *width)
+            })
+            .collect()
+    }
+
+    /// number of terminal lines which a record needs
+    pub fn height(
+        &self,
+        data: &dyn LogData,
+        record: usize,
+        raw: Option<&str>,
+        mask_unicode: bool,
+    ) -> usize {
+        wrapped_height(&self.wrap(data, record, raw, mask_unicode))
+    }
+}
+
+/// number of terminal lines of the highest cell, at least `1`
+pub fn wrapped_height(cells: &[Vec<Line<'_>>]) -> usize {
+    cells.iter().map(Vec::len).max().unwrap_or_default().max(1)
+}
+
+/// Splits a line into lines which are at most `width` columns wide, breaking
+/// between words where possible. Line breaks in the text start a new line as
+/// well. The width is measured after masking, so that escape sequences are
+/// wrapped like any other text.
+pub fn wrap_line(line: Line<'_>, width: usize) -> Vec<Line<'static>> {
+    let mut wrapper = Wrapper {
+        width,
+        style: line.style,
+        lines: Vec::new(),
+        spans: Vec::new(),
+        text: String::new(),
+        span_style: Style::default(),
+        line_width: 0,
+    };
+    for span in line.spans {
+        wrapper.span_style = span.style;
+        for word in span.content.split_word_bounds() {
+            wrapper.push_word(word);
+        }
+        wrapper.finish_span();
+    }
+    wrapper.finish()
+}
+
+struct Wrapper {
+    width: usize,
+
+    /// style of the whole line
+    style: Style,
+    lines: Vec<Line<'static>>,
+
+    /// finished spans of the current line
+    spans: Vec<Span<'static>>,
+
+    /// text and style of the current span
+    text: String,
+    span_style: Style,
+
+    /// width of the current line, including the current span
+    line_width: usize,
+}
+
+impl Wrapper {
+    fn push_word(&mut self, word: &str) {
+        if word == "\n" || word == "\r\n" {
+            self.break_line();
+            return;
+        }
+        let word_width = word.width();
+        if self.line_width + word_width > self.width && self.line_width > 0 {
+            if word.trim().is_empty() {
+                // whitespace at the end of a line is dropped
+                return;
+            }
+            if word_width <= self.width {
+                self.break_line();
+            }
+        }
+        if self.line_width + word_width <= self.width {
+            self.push_str(word, word_width);
+        } else {
+            // the word is too long for a line of its own
+            for grapheme in word.graphemes(true) {
+                let grapheme_width = grapheme.width();
+                if self.line_width + grapheme_width > self.width && self.line_width > 0 {
+                    self.break_line();
+                }
+                self.push_str(grapheme, grapheme_width);
+            }
+        }
+    }
+
+    fn push_str(&mut self, text: &str, width: usize) {
+        self.text.push_str(text);
+        self.line_width += width;
+    }
+
+    fn finish_span(&mut self) {
+        if !self.text.is_empty() {
+            let text = std::mem::take(&mut self.text);
+            self.spans.push(Span::styled(text, self.span_style));
+        }
+    }
+
+    fn break_line(&mut self) {
+        self.finish_span();
+        let spans = std::mem::take(&mut self.spans);
+        self.lines.push(Line::from(spans).style(self.style));
+        self.line_width = 0;
+    }
+
+    fn finish(mut self) -> Vec<Line<'static>> {
+        self.finish_span();
+        if !self.spans.is_empty() || self.lines.is_empty() {
+            self.break_line();
+        }
+        self.lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use clio::ClioPath;
+    use ratatui::{
+        style::{Style, Stylize},
+        text::{Line, Span},
+    };
+    use unicode_width::UnicodeWidthStr;
+
+    use super::{wrap_line, WrapLayout};
+    use crate::{AsMasked, CsvData};
+
+    /// Wraps `text` and returns the text of every line
+    fn wrap(text: &str, width: usize) -> Vec<String> {
+        wrap_line(Line::from(Span::raw(text)), width)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_word_breaks() {
+        assert_eq!(wrap("hello world foo", 11), ["hello world", "foo"]);
+        assert_eq!(wrap("hello world", 20), ["hello world"]);
+        assert_eq!(wrap("", 5), [""]);
+        // whitespace which does not fit is dropped instead of starting a line
+        assert_eq!(wrap("abc   def", 4), ["abc", "def"]);
+    }
+
+    #[test]
+    fn test_long_words() {
+        assert_eq!(wrap("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+        // a word which does not fit on any line fills the current line
+        assert_eq!(wrap("ab abcdefgh", 4), ["ab a", "bcde", "fgh"]);
+    }
+
+    #[test]
+    fn test_line_breaks() {
+        assert_eq!(wrap("ab\ncd", 10), ["ab", "cd"]);
+        assert_eq!(wrap("ab\r\ncd", 10), ["ab", "cd"]);
+        assert_eq!(wrap("ab\n\ncd", 10), ["ab", "", "cd"]);
+    }
+
+    #[test]
+    fn test_wide_characters() {
+        let lines = wrap("日本語テキスト", 5);
+        assert_eq!(lines, ["日本", "語テ", "キス", "ト"]);
+        assert!(lines.iter().all(|line| line.width() <= 5));
+    }
+
+    #[test]
+    fn test_masked_width() {
+        let line = "ab\u{202E}cd".as_masked(.., true, &[]);
+        let masked = line.to_string();
+        assert_eq!(masked, "ab\\u{202e}cd");
+
+        // the escape sequence is measured instead of the invisible character
+        let lines = wrap_line(line, 10);
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| line.width() <= 10));
+        let text: String = lines.iter().map(ToString::to_string).collect();
+        assert_eq!(text, masked);
+    }
+
+    #[test]
+    fn test_styles() {
+        let line = Line::from(vec![Span::raw("ab ").red(), Span::raw("cd")]).bold();
+        let lines = wrap_line(line, 3);
+        assert_eq!(
+            lines,
+            [
+                Line::from(vec![Span::raw("ab ").red()]).bold(),
+                Line::from(vec![Span::styled("cd", Style::default())]).bold(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_layout() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"k,a,b,c\n1,xxxx,yy,\n").unwrap();
+        let data = CsvData::try_from(&ClioPath::new(file.path()).unwrap()).unwrap();
+
+        // empty columns are one character wide
+        let layout = WrapLayout::new(&data, 20);
+        assert_eq!(
+            layout.offsets().collect::<Vec<_>>(),
+            [(0, 4), (5, 2), (8, 1)]
+        );
+
+        // columns which do not fit are shrunk in proportion to their width
+        let layout = WrapLayout::new(&data, 6);
+        assert_eq!(
+            layout.offsets().collect::<Vec<_>>(),
+            [(0, 2), (3, 1), (5, 1)]
+        );
+        let layout = WrapLayout::new(&data, 0);
+        assert_eq!(
+            layout.offsets().collect::<Vec<_>>(),
+            [(0, 1), (2, 1), (4, 1)]
+        );
+
+        // the columns of a record are wrapped separately
+        let cells = layout.wrap(&data, 0, None, false);
+        assert_eq!(cells.len(), 3);
+        assert_eq!(cells[0].len(), 4);
+        assert_eq!(layout.height(&data, 0, None, false), 4);
+        assert_eq!(layout.height(&data, 0, Some("raw"), false), 3);
+    }
+}